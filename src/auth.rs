@@ -0,0 +1,802 @@
+//! This module provides the OAuth2 authorization code flow with PKCE, which is how applications
+//! authenticate users with version 2 of the MyAnimeList API.
+//!
+//! Authorizing a user consists of the following steps:
+//!
+//! 1. Generate a [`PkceChallenge`] and a random state with [`random_state`].
+//! 2. Send the user to the URL returned by [`OAuthClient::authorize_url`].
+//! 3. Wait for MyAnimeList to redirect the user back to the application with an authorization code.
+//!    For CLI applications, a [`LoopbackListener`] can receive the redirect.
+//! 4. Exchange the authorization code for a [`TokenSet`] with [`OAuthClient::exchange_code`].
+//!
+//! Once a [`TokenSet`] has been obtained, it can be given to an [`OAuth`] instance, which will
+//! refresh the access token before it expires and optionally save the new tokens to a file.
+//!
+//! [`PkceChallenge`]: ./struct.PkceChallenge.html
+//! [`random_state`]: ./fn.random_state.html
+//! [`OAuthClient::authorize_url`]: ./struct.OAuthClient.html#method.authorize_url
+//! [`OAuthClient::exchange_code`]: ./struct.OAuthClient.html#method.exchange_code
+//! [`LoopbackListener`]: ./struct.LoopbackListener.html
+//! [`TokenSet`]: ./struct.TokenSet.html
+//! [`OAuth`]: ./struct.OAuth.html
+//!
+//! # Examples
+//!
+//! Authorizing a user from a CLI application:
+//!
+//! ```no_run
+//! use mal::MAL;
+//! use mal::auth::{self, ChallengeMethod, LoopbackListener, OAuth, OAuthClient, PkceChallenge};
+//! use mal::transport::ReqwestTransport;
+//!
+//! // Listen for the redirect on the URI registered for the application
+//! let listener = LoopbackListener::bind(8080).unwrap();
+//!
+//! let mut client = OAuthClient::new("client id");
+//! client.redirect_uri = Some(listener.redirect_uri());
+//!
+//! // MyAnimeList only supports the plain challenge method
+//! let challenge = PkceChallenge::new(ChallengeMethod::Plain).unwrap();
+//! let state = auth::random_state().unwrap();
+//!
+//! println!("Please visit {}", client.authorize_url(&challenge, &state));
+//!
+//! let code = listener.wait_for_code(&state).unwrap();
+//! let tokens = client
+//!     .exchange_code(&ReqwestTransport::default(), &code, &challenge)
+//!     .unwrap();
+//!
+//! // Remember the tokens so the user doesn't have to authorize the application again
+//! tokens.save("tokens.json").unwrap();
+//!
+//! let oauth = OAuth::new(client, tokens).with_token_file("tokens.json");
+//! let mal = MAL::with_oauth("username", oauth);
+//!
+//! let list = mal.anime_list().read().unwrap();
+//! ```
+
+use base64;
+use chrono::{DateTime, Duration as ChronoDuration, TimeZone, Utc};
+use error::{AuthError, RequestError};
+use rand::{OsRng, Rng};
+use reqwest::{Method, StatusCode, Url};
+use reqwest::header::ContentType;
+use serde_json::{self, Value};
+use sha2::{Digest, Sha256};
+use std::fmt::{self, Debug};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use transport::{HttpRequest, Transport};
+use url::form_urlencoded;
+
+lazy_static! {
+    static ref AUTHORIZE_URL: Url =
+        Url::parse("https://myanimelist.net/v1/oauth2/authorize").unwrap();
+    static ref TOKEN_URL: Url = Url::parse("https://myanimelist.net/v1/oauth2/token").unwrap();
+}
+
+// The characters that are allowed in a code verifier, as defined by RFC 7636
+const VERIFIER_CHARS: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~";
+
+const VERIFIER_LEN: usize = 128;
+const STATE_LEN: usize = 32;
+
+const CALLBACK_PATH: &str = "/callback";
+const DEFAULT_CALLBACK_TIMEOUT_SECS: u64 = 5 * 60;
+
+// How often the loopback listener checks for a new connection
+const ACCEPT_POLL_INTERVAL_MS: u64 = 50;
+// How long a connection to the loopback listener has to send its request line, since browsers
+// may open connections ahead of time without sending anything on them
+const CONNECTION_READ_TIMEOUT_SECS: u64 = 2;
+
+// Generates a random string with `len` characters from the specified character set
+fn random_string(len: usize, chars: &[u8]) -> Result<String, AuthError> {
+    let mut rng = OsRng::new().map_err(AuthError::Io)?;
+
+    let string = (0..len)
+        .map(|_| chars[rng.gen_range(0, chars.len())] as char)
+        .collect();
+
+    Ok(string)
+}
+
+/// Generates a random value to use as the `state` parameter of an authorization request.
+///
+/// The state is sent back by MyAnimeList along with the authorization code, and should be
+/// checked to make sure the redirect came from an authorization request the application made.
+#[inline]
+pub fn random_state() -> Result<String, AuthError> {
+    random_string(STATE_LEN, VERIFIER_CHARS)
+}
+
+/// The method used to derive a PKCE code challenge from its verifier.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ChallengeMethod {
+    /// The challenge is the verifier itself. This is the only method MyAnimeList supports.
+    Plain,
+    /// The challenge is the base64url-encoded SHA-256 hash of the verifier.
+    S256,
+}
+
+impl ChallengeMethod {
+    /// Returns the name of the method that is sent to the authorization server.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ChallengeMethod::Plain => "plain",
+            ChallengeMethod::S256 => "S256",
+        }
+    }
+}
+
+impl Default for ChallengeMethod {
+    #[inline]
+    fn default() -> Self {
+        ChallengeMethod::Plain
+    }
+}
+
+/// A PKCE code verifier and the challenge derived from it.
+///
+/// The challenge is sent with the authorization request, and the verifier is sent when
+/// exchanging the authorization code for tokens.
+///
+/// # Examples
+///
+/// ```
+/// use mal::auth::{ChallengeMethod, PkceChallenge};
+///
+/// let plain = PkceChallenge::new(ChallengeMethod::Plain).unwrap();
+/// assert_eq!(plain.verifier.len(), 128);
+/// assert_eq!(plain.challenge, plain.verifier);
+///
+/// let hashed = PkceChallenge::from_verifier(
+///     "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk",
+///     ChallengeMethod::S256,
+/// );
+///
+/// assert_eq!(hashed.challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct PkceChallenge {
+    /// The secret code verifier.
+    pub verifier: String,
+    /// The code challenge derived from the verifier.
+    pub challenge: String,
+    /// The method used to derive the challenge.
+    pub method: ChallengeMethod,
+}
+
+impl PkceChallenge {
+    /// Creates a new `PkceChallenge` with a random verifier.
+    #[inline]
+    pub fn new(method: ChallengeMethod) -> Result<PkceChallenge, AuthError> {
+        let verifier = random_string(VERIFIER_LEN, VERIFIER_CHARS)?;
+        Ok(PkceChallenge::from_verifier(verifier, method))
+    }
+
+    /// Creates a new `PkceChallenge` from an existing verifier.
+    pub fn from_verifier<S: Into<String>>(verifier: S, method: ChallengeMethod) -> PkceChallenge {
+        let verifier = verifier.into();
+
+        let challenge = match method {
+            ChallengeMethod::Plain => verifier.clone(),
+            ChallengeMethod::S256 => {
+                let hash = Sha256::digest(verifier.as_bytes());
+                base64::encode_config(&hash, base64::URL_SAFE_NO_PAD)
+            }
+        };
+
+        PkceChallenge {
+            verifier,
+            challenge,
+            method,
+        }
+    }
+}
+
+// Automatically deriving Debug will display the verifier
+impl Debug for PkceChallenge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PkceChallenge {{ method: {:?} }}", self.method)
+    }
+}
+
+/// The tokens returned by the authorization server.
+///
+/// # Examples
+///
+/// ```
+/// use mal::auth::TokenSet;
+///
+/// let tokens = TokenSet::new("access token");
+/// let json = tokens.to_json().to_string();
+///
+/// assert_eq!(TokenSet::from_json(&json).unwrap(), tokens);
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct TokenSet {
+    /// The token that is sent with every request.
+    pub access_token: String,
+    /// The token used to get a new access token once it expires.
+    pub refresh_token: Option<String>,
+    /// The type of the access token. This should always be "Bearer".
+    pub token_type: String,
+    /// The time the access token expires at.
+    /// If this isn't set, the access token is assumed to never expire.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl TokenSet {
+    /// Creates a new `TokenSet` with the specified access token that never expires.
+    #[inline]
+    pub fn new<S: Into<String>>(access_token: S) -> TokenSet {
+        TokenSet {
+            access_token: access_token.into(),
+            refresh_token: None,
+            token_type: "Bearer".into(),
+            expires_at: None,
+        }
+    }
+
+    /// Returns true if the access token will expire within the specified duration.
+    pub fn expires_within(&self, duration: Duration) -> bool {
+        match self.expires_at {
+            Some(expires_at) => {
+                let margin = ChronoDuration::from_std(duration)
+                    .unwrap_or_else(|_| ChronoDuration::zero());
+                expires_at - margin <= Utc::now()
+            }
+            None => false,
+        }
+    }
+
+    /// Returns true if the access token has expired.
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(Duration::from_secs(0))
+    }
+
+    /// Parses a `TokenSet` that was previously converted to JSON with [`to_json`].
+    ///
+    /// [`to_json`]: #method.to_json
+    pub fn from_json(json: &str) -> Result<TokenSet, AuthError> {
+        let root: Value = serde_json::from_str(json).map_err(AuthError::Json)?;
+
+        let tokens = TokenSet {
+            access_token: json_string(&root, "access_token")?,
+            refresh_token: json_string(&root, "refresh_token").ok(),
+            token_type: json_string(&root, "token_type")?,
+            expires_at: root.get("expires_at")
+                .and_then(|time| time.as_i64())
+                .map(|time| Utc.timestamp(time, 0)),
+        };
+
+        Ok(tokens)
+    }
+
+    /// Converts the `TokenSet` to JSON so it can be stored and restored later with [`from_json`].
+    ///
+    /// [`from_json`]: #method.from_json
+    pub fn to_json(&self) -> Value {
+        json!({
+            "access_token": self.access_token,
+            "refresh_token": self.refresh_token,
+            "token_type": self.token_type,
+            "expires_at": self.expires_at.map(|time| time.timestamp()),
+        })
+    }
+
+    /// Loads a `TokenSet` from a file that was written by [`save`].
+    ///
+    /// [`save`]: #method.save
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<TokenSet, AuthError> {
+        let file = File::open(path).map_err(AuthError::Io)?;
+        let root: Value = serde_json::from_reader(file).map_err(AuthError::Json)?;
+
+        TokenSet::from_json(&root.to_string())
+    }
+
+    /// Saves the `TokenSet` to a file as JSON.
+    ///
+    /// Since the file will contain the user's tokens, it should be kept private.
+    /// On Unix, the file is made readable and writable only by its owner.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AuthError> {
+        let mut file = create_private(path.as_ref()).map_err(AuthError::Io)?;
+
+        file.write_all(self.to_json().to_string().as_bytes())
+            .map_err(AuthError::Io)
+    }
+
+    // Parses the response of a token request
+    fn from_response(json: &str) -> Result<TokenSet, AuthError> {
+        let root: Value = serde_json::from_str(json).map_err(AuthError::Json)?;
+
+        let tokens = TokenSet {
+            access_token: json_string(&root, "access_token")?,
+            refresh_token: json_string(&root, "refresh_token").ok(),
+            token_type: json_string(&root, "token_type").unwrap_or_else(|_| "Bearer".into()),
+            expires_at: root.get("expires_in")
+                .and_then(|secs| secs.as_i64())
+                .map(|secs| Utc::now() + ChronoDuration::seconds(secs)),
+        };
+
+        Ok(tokens)
+    }
+}
+
+// Automatically deriving Debug will display the tokens
+impl Debug for TokenSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TokenSet {{ token_type: {:?}, expires_at: {:?} }}",
+            self.token_type, self.expires_at
+        )
+    }
+}
+
+/// An application registered with an OAuth2 authorization server.
+///
+/// By default, the authorization and token endpoints of MyAnimeList are used.
+#[derive(Clone, PartialEq)]
+pub struct OAuthClient {
+    /// The ID of the application.
+    pub client_id: String,
+    /// The secret of the application. Applications that can't keep a secret, such as
+    /// CLI applications, should leave this unset.
+    pub client_secret: Option<String>,
+    /// The URI the user is sent back to after authorizing the application.
+    /// If the application only has one redirect URI registered, this can be left unset.
+    pub redirect_uri: Option<String>,
+    /// The URL the user is sent to in order to authorize the application.
+    pub authorize_url: Url,
+    /// The URL that authorization codes and refresh tokens are exchanged at.
+    pub token_url: Url,
+}
+
+impl OAuthClient {
+    /// Creates a new `OAuthClient` for the application with the specified ID.
+    #[inline]
+    pub fn new<S: Into<String>>(client_id: S) -> OAuthClient {
+        OAuthClient {
+            client_id: client_id.into(),
+            client_secret: None,
+            redirect_uri: None,
+            authorize_url: AUTHORIZE_URL.clone(),
+            token_url: TOKEN_URL.clone(),
+        }
+    }
+
+    /// Returns the URL the user should visit to authorize the application.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mal::auth::{ChallengeMethod, OAuthClient, PkceChallenge};
+    ///
+    /// let client = OAuthClient::new("client id");
+    /// let challenge = PkceChallenge::from_verifier("verifier", ChallengeMethod::Plain);
+    ///
+    /// let url = client.authorize_url(&challenge, "state");
+    ///
+    /// assert_eq!(
+    ///     url.as_str(),
+    ///     "https://myanimelist.net/v1/oauth2/authorize?response_type=code&client_id=client+id\
+    ///      &code_challenge=verifier&code_challenge_method=plain&state=state"
+    /// );
+    /// ```
+    pub fn authorize_url(&self, challenge: &PkceChallenge, state: &str) -> Url {
+        let mut url = self.authorize_url.clone();
+
+        {
+            let mut query = url.query_pairs_mut();
+
+            query
+                .append_pair("response_type", "code")
+                .append_pair("client_id", &self.client_id)
+                .append_pair("code_challenge", &challenge.challenge)
+                .append_pair("code_challenge_method", challenge.method.as_str())
+                .append_pair("state", state);
+
+            if let Some(ref redirect_uri) = self.redirect_uri {
+                query.append_pair("redirect_uri", redirect_uri);
+            }
+        }
+
+        url
+    }
+
+    /// Exchanges an authorization code for a new [`TokenSet`].
+    ///
+    /// The `challenge` must be the same one that was used to build the authorization URL.
+    ///
+    /// [`TokenSet`]: ./struct.TokenSet.html
+    pub fn exchange_code(
+        &self,
+        transport: &dyn Transport,
+        code: &str,
+        challenge: &PkceChallenge,
+    ) -> Result<TokenSet, AuthError> {
+        let mut fields = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("code_verifier", &challenge.verifier),
+        ];
+
+        if let Some(ref redirect_uri) = self.redirect_uri {
+            fields.push(("redirect_uri", redirect_uri));
+        }
+
+        self.request_tokens(transport, fields)
+    }
+
+    /// Uses the refresh token of `tokens` to get a new [`TokenSet`].
+    ///
+    /// If the server doesn't send back a new refresh token, the current one is kept.
+    ///
+    /// [`TokenSet`]: ./struct.TokenSet.html
+    pub fn refresh(
+        &self,
+        transport: &dyn Transport,
+        tokens: &TokenSet,
+    ) -> Result<TokenSet, AuthError> {
+        let refresh_token = match tokens.refresh_token {
+            Some(ref token) => token,
+            None => return Err(AuthError::NoRefreshToken),
+        };
+
+        let fields = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+        ];
+
+        let mut new_tokens = self.request_tokens(transport, fields)?;
+
+        if new_tokens.refresh_token.is_none() {
+            new_tokens.refresh_token = tokens.refresh_token.clone();
+        }
+
+        Ok(new_tokens)
+    }
+
+    fn request_tokens<'b>(
+        &'b self,
+        transport: &dyn Transport,
+        mut fields: Vec<(&'b str, &'b str)>,
+    ) -> Result<TokenSet, AuthError> {
+        fields.push(("client_id", &self.client_id));
+
+        if let Some(ref secret) = self.client_secret {
+            fields.push(("client_secret", secret));
+        }
+
+        let body = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(fields)
+            .finish();
+
+        let mut req = HttpRequest::new(Method::Post, self.token_url.clone());
+        req.headers.set(ContentType::form_url_encoded());
+        req.body = Some(body);
+
+        let resp = transport.send(req).map_err(AuthError::Request)?;
+
+        match resp.status {
+            StatusCode::Ok => TokenSet::from_response(&resp.body),
+            status => {
+                let error = serde_json::from_str::<Value>(&resp.body)
+                    .ok()
+                    .and_then(|root| json_string(&root, "error").ok());
+
+                match error {
+                    Some(error) => Err(AuthError::Rejected(error)),
+                    None => Err(AuthError::Request(RequestError::BadResponseCode(status))),
+                }
+            }
+        }
+    }
+}
+
+// Automatically deriving Debug will display the client secret
+impl Debug for OAuthClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "OAuthClient {{ client_id: {:?}, redirect_uri: {:?}, authorize_url: {:?}, token_url: {:?} }}",
+            self.client_id,
+            self.redirect_uri,
+            self.authorize_url.as_str(),
+            self.token_url.as_str()
+        )
+    }
+}
+
+/// Keeps the tokens of a user up to date.
+///
+/// When used with a [`MAL`] instance, the access token will be refreshed automatically
+/// before it expires.
+///
+/// Cloning an `OAuth` instance will return a handle to the same tokens.
+///
+/// [`MAL`]: ../struct.MAL.html
+#[derive(Debug, Clone)]
+pub struct OAuth {
+    /// The application the tokens were issued to.
+    pub client: OAuthClient,
+    /// How long before the access token expires it should be refreshed.
+    pub refresh_margin: Duration,
+    tokens: Arc<Mutex<TokenSet>>,
+    // Held while refreshing so only one thread sends a refresh request at a time,
+    // without blocking threads that only need the current access token
+    refresh_lock: Arc<Mutex<()>>,
+    token_file: Option<PathBuf>,
+}
+
+impl OAuth {
+    /// Creates a new `OAuth` instance that will keep the specified tokens up to date.
+    ///
+    /// By default, the access token will be refreshed 5 minutes before it expires.
+    #[inline]
+    pub fn new(client: OAuthClient, tokens: TokenSet) -> OAuth {
+        OAuth {
+            client,
+            refresh_margin: Duration::from_secs(5 * 60),
+            tokens: Arc::new(Mutex::new(tokens)),
+            refresh_lock: Arc::new(Mutex::new(())),
+            token_file: None,
+        }
+    }
+
+    /// Saves the tokens to the specified file every time they are refreshed.
+    #[inline]
+    pub fn with_token_file<P: Into<PathBuf>>(mut self, path: P) -> OAuth {
+        self.token_file = Some(path.into());
+        self
+    }
+
+    /// Returns the current tokens.
+    #[inline]
+    pub fn tokens(&self) -> TokenSet {
+        self.tokens.lock().unwrap().clone()
+    }
+
+    /// Returns the current access token.
+    #[inline]
+    pub fn access_token(&self) -> String {
+        self.tokens.lock().unwrap().access_token.clone()
+    }
+
+    /// Refreshes the access token if it will expire within the refresh margin.
+    ///
+    /// If the tokens can't be refreshed, such as when there is no refresh token, an error is only
+    /// returned once the access token has actually expired.
+    ///
+    /// Returns true if the tokens were refreshed.
+    pub fn refresh_if_needed(&self, transport: &dyn Transport) -> Result<bool, AuthError> {
+        let _refreshing = self.refresh_lock.lock().unwrap();
+
+        // Another thread may have refreshed the tokens while this one was waiting
+        let tokens = self.tokens();
+
+        if !tokens.expires_within(self.refresh_margin) {
+            return Ok(false);
+        }
+
+        let new_tokens = match self.client.refresh(transport, &tokens) {
+            Ok(new_tokens) => new_tokens,
+            Err(_) if !tokens.is_expired() => return Ok(false),
+            Err(err) => return Err(err),
+        };
+
+        *self.tokens.lock().unwrap() = new_tokens.clone();
+
+        if let Some(ref path) = self.token_file {
+            new_tokens.save(path)?;
+        }
+
+        Ok(true)
+    }
+}
+
+/// Receives the redirect from the authorization server on a local port.
+///
+/// This is meant for CLI applications, which can register a redirect URI such as
+/// `http://localhost:8080/callback` with MyAnimeList and open the authorization URL in the
+/// user's browser.
+#[derive(Debug)]
+pub struct LoopbackListener {
+    listener: TcpListener,
+    timeout: Duration,
+}
+
+impl LoopbackListener {
+    /// Starts listening for the redirect on the specified port of the loopback interface.
+    ///
+    /// Passing 0 as the port will let the OS pick an unused one.
+    /// The listener will wait 5 minutes for the redirect by default.
+    #[inline]
+    pub fn bind(port: u16) -> Result<LoopbackListener, AuthError> {
+        let addr = SocketAddr::new(Ipv4Addr::new(127, 0, 0, 1).into(), port);
+        let listener = TcpListener::bind(addr).map_err(AuthError::Io)?;
+
+        Ok(LoopbackListener {
+            listener,
+            timeout: Duration::from_secs(DEFAULT_CALLBACK_TIMEOUT_SECS),
+        })
+    }
+
+    /// Sets how long [`wait_for_code`] waits for the redirect before giving up.
+    ///
+    /// [`wait_for_code`]: #method.wait_for_code
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Duration) -> LoopbackListener {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns the port the listener is bound to.
+    #[inline]
+    pub fn port(&self) -> u16 {
+        self.listener
+            .local_addr()
+            .map(|addr| addr.port())
+            .unwrap_or(0)
+    }
+
+    /// Returns the redirect URI that points to the listener.
+    #[inline]
+    pub fn redirect_uri(&self) -> String {
+        format!("http://localhost:{}{}", self.port(), CALLBACK_PATH)
+    }
+
+    /// Blocks until the authorization server redirects the user back to the listener, and
+    /// returns the authorization code it sent.
+    ///
+    /// Requests for anything other than the redirect URI, such as the favicon a browser asks
+    /// for, are ignored. If no redirect is received before the listener's timeout,
+    /// [`AuthError::CallbackTimeout`] is returned.
+    ///
+    /// The `state` must be the same one that was used to build the authorization URL.
+    ///
+    /// [`AuthError::CallbackTimeout`]: ../error/enum.AuthError.html#variant.CallbackTimeout
+    pub fn wait_for_code(&self, state: &str) -> Result<String, AuthError> {
+        let deadline = Instant::now() + self.timeout;
+
+        self.listener.set_nonblocking(true).map_err(AuthError::Io)?;
+
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    if Instant::now() >= deadline {
+                        return Err(AuthError::CallbackTimeout);
+                    }
+
+                    thread::sleep(Duration::from_millis(ACCEPT_POLL_INTERVAL_MS));
+                    continue;
+                }
+                Err(err) => return Err(AuthError::Io(err)),
+            };
+
+            let request_line = match read_request_line(&stream) {
+                Some(line) => line,
+                None => continue,
+            };
+
+            if request_path(&request_line) != Some(CALLBACK_PATH) {
+                respond(&stream, "404 Not Found", "Not found.").ok();
+                continue;
+            }
+
+            let result = parse_callback(&request_line, state);
+
+            let message = match result {
+                Ok(_) => "Authorization complete. You may now close this window.",
+                Err(_) => "Authorization failed. Please return to the application.",
+            };
+
+            respond(&stream, "200 OK", message).map_err(AuthError::Io)?;
+            return result;
+        }
+    }
+}
+
+// Returns None if the connection didn't send a request line in time
+fn read_request_line(stream: &TcpStream) -> Option<String> {
+    stream.set_nonblocking(false).ok()?;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(CONNECTION_READ_TIMEOUT_SECS)))
+        .ok()?;
+
+    let mut request_line = String::new();
+    BufReader::new(stream).read_line(&mut request_line).ok()?;
+
+    Some(request_line)
+}
+
+// Returns the path of a request line such as "GET /callback?code=... HTTP/1.1", without its query
+fn request_path(request_line: &str) -> Option<&str> {
+    let target = request_line.split_whitespace().nth(1)?;
+    target.split('?').next()
+}
+
+fn respond(mut stream: &TcpStream, status: &str, message: &str) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        message.len(),
+        message
+    );
+
+    stream.write_all(response.as_bytes())
+}
+
+// Parses the authorization code out of a request line such as "GET /callback?code=... HTTP/1.1"
+fn parse_callback(request_line: &str, expected_state: &str) -> Result<String, AuthError> {
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .ok_or(AuthError::BadCallback)?;
+
+    let url = Url::parse("http://localhost")
+        .and_then(|base| base.join(path))
+        .map_err(|_| AuthError::BadCallback)?;
+
+    let mut code = None;
+    let mut state = None;
+
+    for (name, value) in url.query_pairs() {
+        match name.as_ref() {
+            "code" => code = Some(value.into_owned()),
+            "state" => state = Some(value.into_owned()),
+            "error" => return Err(AuthError::AuthorizationDenied(value.into_owned())),
+            _ => (),
+        }
+    }
+
+    if state.as_ref().map(String::as_str) != Some(expected_state) {
+        return Err(AuthError::StateMismatch);
+    }
+
+    code.ok_or(AuthError::BadCallback)
+}
+
+#[cfg(unix)]
+fn create_private(path: &Path) -> io::Result<File> {
+    use std::fs::Permissions;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+
+    // The mode is only used when the file is created, so an existing file has to be changed
+    file.set_permissions(Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn create_private(path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+}
+
+fn json_string(json: &Value, name: &str) -> Result<String, AuthError> {
+    json.get(name)
+        .and_then(|value| value.as_str())
+        .map(String::from)
+        .ok_or_else(|| AuthError::MissingField(name.into()))
+}
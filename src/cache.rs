@@ -0,0 +1,448 @@
+//! This module provides a file-based cache for lists and search results, so they don't have to be
+//! requested from MyAnimeList every time they're needed.
+//!
+//! A [`Cache`] can be attached to a [`MAL`] instance through its `cache` field or
+//! [`MALBuilder::cache`]. Every [`List`] created from that instance will then use a user's cached
+//! list and cached search results until they're older than the TTL set for them. Adding, updating,
+//! and deleting entries will also update the cached list in place, so reading the list afterwards
+//! doesn't have to request it again.
+//!
+//! The cache is only an optimization: if it can't be read or written to, requests are sent to
+//! MyAnimeList as if there was no cache.
+//!
+//! [`Cache`]: ./struct.Cache.html
+//! [`MAL`]: ../struct.MAL.html
+//! [`MALBuilder::cache`]: ../struct.MALBuilder.html#method.cache
+//! [`List`]: ../list/struct.List.html
+
+use list::{EntryValues, ListEntries, ListEntry};
+use request::ListType;
+use serde_json::{self, Map, Value};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use {Api, MAL};
+
+const DEFAULT_LIST_TTL_SECS: u64 = 15 * 60;
+const DEFAULT_SEARCH_TTL_SECS: u64 = 24 * 60 * 60;
+
+// Used to give every temporary file written by this process a different name
+static TMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Stores lists and search results as JSON files in a directory.
+///
+/// Each user's anime and manga list is stored in its own file, while search results are stored
+/// in one file per list type. Lists and search results from different versions of the API or
+/// base URLs are stored in separate files, so switching between them never returns the wrong data.
+///
+/// Cloning a `Cache` will return a handle to the same directory.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mal::MALBuilder;
+/// use mal::cache::Cache;
+/// use std::time::Duration;
+///
+/// // Cache lists for an hour and search results for a week
+/// let cache = Cache::new("/tmp/mal-cache")
+///     .with_list_ttl(Duration::from_secs(60 * 60))
+///     .with_search_ttl(Duration::from_secs(7 * 24 * 60 * 60));
+///
+/// let mal = MALBuilder::new("username", "password")
+///     .cache(cache)
+///     .build()
+///     .unwrap();
+///
+/// // Only the first read will send a request
+/// let list = mal.anime_list().read().unwrap();
+/// let list = mal.anime_list().read().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+    list_ttl: Duration,
+    search_ttl: Duration,
+    lock: Arc<Mutex<()>>,
+}
+
+impl Cache {
+    /// Creates a new `Cache` that stores its files in the specified directory.
+    ///
+    /// The directory will be created when something is first written to the cache.
+    /// Lists are cached for 15 minutes and search results for a day by default.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Cache {
+        Cache {
+            dir: dir.into(),
+            list_ttl: Duration::from_secs(DEFAULT_LIST_TTL_SECS),
+            search_ttl: Duration::from_secs(DEFAULT_SEARCH_TTL_SECS),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Sets how long a user's list is used before it's requested again.
+    ///
+    /// A TTL of zero disables caching lists.
+    #[inline]
+    pub fn with_list_ttl(mut self, ttl: Duration) -> Cache {
+        self.list_ttl = ttl;
+        self
+    }
+
+    /// Sets how long search results are used before they're requested again.
+    ///
+    /// A TTL of zero disables caching search results.
+    #[inline]
+    pub fn with_search_ttl(mut self, ttl: Duration) -> Cache {
+        self.search_ttl = ttl;
+        self
+    }
+
+    /// Returns the directory the cache stores its files in.
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns how long a user's list is cached for.
+    #[inline]
+    pub fn list_ttl(&self) -> Duration {
+        self.list_ttl
+    }
+
+    /// Returns how long search results are cached for.
+    #[inline]
+    pub fn search_ttl(&self) -> Duration {
+        self.search_ttl
+    }
+
+    /// Removes every cached list and search result.
+    pub fn clear(&self) -> io::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        for file in dir {
+            let path = file?.path();
+
+            let is_cache_file = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.ends_with(".list.json") || name.ends_with(".search.json"))
+                .unwrap_or(false);
+
+            if is_cache_file {
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn list<E: ListEntry>(&self, mal: &MAL) -> Option<ListEntries<E>> {
+        let _guard = self.lock.lock().unwrap();
+        let cached = self.load(&self.list_path::<E>(mal))?;
+
+        if !is_fresh(&cached, self.list_ttl) {
+            return None;
+        }
+
+        E::list_from_json(cached.data).ok()
+    }
+
+    pub(crate) fn store_list<E: ListEntry>(&self, mal: &MAL, list: &ListEntries<E>) {
+        if self.list_ttl == Duration::from_secs(0) {
+            return;
+        }
+
+        let _guard = self.lock.lock().unwrap();
+
+        if let Ok(data) = E::list_to_json(list) {
+            let cached = Cached::new(data);
+            self.save(&self.list_path::<E>(mal), &cached);
+        }
+    }
+
+    pub(crate) fn insert_entry<E: ListEntry>(&self, mal: &MAL, entry: &E) {
+        self.modify_list::<E, _>(mal, |json, list| {
+            if list.entries.iter().any(|e| e.id() == entry.id()) {
+                return true;
+            }
+
+            match (json.get_mut("entries"), entry.entry_to_json()) {
+                (Some(&mut Value::Array(ref mut entries)), Ok(entry)) => {
+                    entries.push(entry);
+                    true
+                }
+                _ => false,
+            }
+        });
+    }
+
+    pub(crate) fn update_entry<E: ListEntry>(&self, mal: &MAL, id: u32, values: &E::Values) {
+        self.modify_list::<E, _>(mal, |json, mut list| {
+            if let Some(entry) = list.entries.iter_mut().find(|e| e.id() == id) {
+                values.copy_changed_to(entry.values_mut());
+                entry.set_last_updated_time();
+            }
+
+            match E::list_to_json(&list) {
+                Ok(updated) => {
+                    *json = updated;
+                    true
+                }
+                Err(_) => false,
+            }
+        });
+    }
+
+    pub(crate) fn remove_entry<E: ListEntry>(&self, mal: &MAL, id: u32) {
+        self.modify_list::<E, _>(mal, |json, mut list| {
+            list.entries.retain(|e| e.id() != id);
+
+            match E::list_to_json(&list) {
+                Ok(updated) => {
+                    *json = updated;
+                    true
+                }
+                Err(_) => false,
+            }
+        });
+    }
+
+    pub(crate) fn invalidate_list<E: ListEntry>(&self, mal: &MAL) {
+        let _guard = self.lock.lock().unwrap();
+        fs::remove_file(self.list_path::<E>(mal)).ok();
+    }
+
+    pub(crate) fn search_results<E: ListEntry>(
+        &self,
+        mal: &MAL,
+        query: &str,
+    ) -> Option<Vec<E::Info>> {
+        let _guard = self.lock.lock().unwrap();
+        let mut searches = self.load_searches::<E>(mal);
+
+        match searches.remove(&search_key(query)) {
+            Some(cached) if is_fresh(&cached, self.search_ttl) => {
+                E::search_results_from_json(cached.data).ok()
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn store_search_results<E: ListEntry>(
+        &self,
+        mal: &MAL,
+        query: &str,
+        results: &[E::Info],
+    ) {
+        if self.search_ttl == Duration::from_secs(0) {
+            return;
+        }
+
+        let data = match E::search_results_to_json(results) {
+            Ok(data) => data,
+            Err(_) => return,
+        };
+
+        let _guard = self.lock.lock().unwrap();
+        let mut searches = self.load_searches::<E>(mal);
+
+        // Expired results would never be used again, so there's no point in keeping them around
+        let ttl = self.search_ttl;
+        searches.retain(|_, cached| is_fresh(cached, ttl));
+        searches.insert(search_key(query), Cached::new(data));
+
+        let searches = searches
+            .into_iter()
+            .map(|(query, cached)| (query, cached.to_json()))
+            .collect::<Map<_, _>>();
+
+        let path = self.search_path::<E>(mal);
+        self.write_json(&path, &Value::Object(searches));
+    }
+
+    // Loads the cached list regardless of its age, lets `modify` change it, and saves it
+    // if `modify` returns true.
+    // The list is passed both as JSON and parsed so entries can be added without cloning them.
+    fn modify_list<E, F>(&self, mal: &MAL, modify: F)
+    where
+        E: ListEntry,
+        F: FnOnce(&mut Value, ListEntries<E>) -> bool,
+    {
+        let _guard = self.lock.lock().unwrap();
+        let path = self.list_path::<E>(mal);
+
+        let mut cached = match self.load(&path) {
+            Some(cached) => cached,
+            None => return,
+        };
+
+        let list = match E::list_from_json(cached.data.clone()) {
+            Ok(list) => list,
+            Err(_) => return,
+        };
+
+        if modify(&mut cached.data, list) {
+            self.save(&path, &cached);
+        } else {
+            // Leaving the list as-is would make it inconsistent with the user's actual list
+            fs::remove_file(path).ok();
+        }
+    }
+
+    fn load_searches<E: ListEntry>(&self, mal: &MAL) -> HashMap<String, Cached> {
+        let json = File::open(self.search_path::<E>(mal))
+            .ok()
+            .and_then(|file| serde_json::from_reader(file).ok());
+
+        match json {
+            Some(Value::Object(searches)) => searches
+                .into_iter()
+                .filter_map(|(query, json)| Cached::from_json(json).map(|cached| (query, cached)))
+                .collect(),
+            _ => HashMap::new(),
+        }
+    }
+
+    fn load(&self, path: &Path) -> Option<Cached> {
+        let file = File::open(path).ok()?;
+        let json = serde_json::from_reader(file).ok()?;
+        Cached::from_json(json)
+    }
+
+    fn save(&self, path: &Path, cached: &Cached) {
+        self.write_json(path, &cached.to_json());
+    }
+
+    // Writes to a temporary file first so a failed write can't leave a truncated file behind.
+    // Every write uses its own temporary file, since other processes may share the directory.
+    fn write_json(&self, path: &Path, json: &Value) {
+        let tmp_path = path.with_extension(format!(
+            "{}.{}.tmp",
+            process::id(),
+            TMP_FILE_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+
+        let result = fs::create_dir_all(&self.dir)
+            .and_then(|_| File::create(&tmp_path))
+            .and_then(|file| serde_json::to_writer(file, json).map_err(io::Error::from))
+            .and_then(|_| fs::rename(&tmp_path, path));
+
+        if result.is_err() {
+            fs::remove_file(&tmp_path).ok();
+        }
+    }
+
+    fn list_path<E: ListEntry>(&self, mal: &MAL) -> PathBuf {
+        let name = format!(
+            "{}.{}.{}.list.json",
+            file_safe(&mal.username),
+            list_name::<E>(),
+            api_key(mal)
+        );
+
+        self.dir.join(name)
+    }
+
+    fn search_path<E: ListEntry>(&self, mal: &MAL) -> PathBuf {
+        self.dir
+            .join(format!("{}.{}.search.json", list_name::<E>(), api_key(mal)))
+    }
+}
+
+// A cached value along with the time it was requested, in seconds since the Unix epoch
+struct Cached {
+    fetched_at: u64,
+    data: Value,
+}
+
+impl Cached {
+    fn new(data: Value) -> Cached {
+        Cached {
+            fetched_at: unix_time(SystemTime::now()),
+            data,
+        }
+    }
+
+    fn from_json(json: Value) -> Option<Cached> {
+        match json {
+            Value::Object(mut obj) => Some(Cached {
+                fetched_at: obj.get("fetched_at")?.as_u64()?,
+                data: obj.remove("data")?,
+            }),
+            _ => None,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "fetched_at": self.fetched_at,
+            "data": self.data,
+        })
+    }
+}
+
+fn is_fresh(cached: &Cached, ttl: Duration) -> bool {
+    let now = unix_time(SystemTime::now());
+    now.saturating_sub(cached.fetched_at) < ttl.as_secs()
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0)
+}
+
+fn search_key(query: &str) -> String {
+    query.trim().to_lowercase()
+}
+
+fn list_name<E: ListEntry>() -> &'static str {
+    match E::list_type() {
+        ListType::Anime => "anime",
+        ListType::Manga => "manga",
+    }
+}
+
+// Identifies the version of the API and the URL requests are sent to, such as "v2-1a2b3c4d5e6f7a8b"
+fn api_key(mal: &MAL) -> String {
+    let (name, base_url) = match mal.api {
+        Api::Legacy => ("legacy", &mal.base_url),
+        Api::V2 { .. } => ("v2", &mal.v2_base_url),
+    };
+
+    format!("{}-{:016x}", name, fnv1a(base_url.as_str().as_bytes()))
+}
+
+// The standard library's hasher isn't guaranteed to give the same result between releases,
+// which would change the name of every cache file
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// Usernames on MyAnimeList can only contain letters, numbers, underscores and dashes,
+// but the username of a MAL instance can be set to anything
+fn file_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
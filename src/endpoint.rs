@@ -0,0 +1,184 @@
+//! This module contains the paths [`MAL`] sends requests to when using the legacy API.
+//!
+//! Every path can be changed to point requests at a caching proxy, a mirror, or a local fake.
+//! The easiest way to do this is through [`MALBuilder::endpoint`].
+//!
+//! [`MAL`]: ../struct.MAL.html
+//! [`MALBuilder::endpoint`]: ../struct.MALBuilder.html#method.endpoint
+
+use error::ConfigError;
+use reqwest::Url;
+use std::collections::HashMap;
+
+/// The placeholder in a path that is replaced with the ID of a series.
+pub const ID_PLACEHOLDER: &str = "{id}";
+
+/// Represents an endpoint of the legacy API.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// Searches for anime.
+    AnimeSearch,
+    /// Searches for manga.
+    MangaSearch,
+    /// Returns the entries on a user's anime or manga list.
+    List,
+    /// Adds an anime to the user's list.
+    AnimeAdd,
+    /// Adds a manga to the user's list.
+    MangaAdd,
+    /// Updates an anime on the user's list.
+    AnimeUpdate,
+    /// Updates a manga on the user's list.
+    MangaUpdate,
+    /// Removes an anime from the user's list.
+    AnimeDelete,
+    /// Removes a manga from the user's list.
+    MangaDelete,
+    /// Checks if the user's credentials are valid.
+    VerifyCredentials,
+}
+
+impl Endpoint {
+    /// Every endpoint of the legacy API.
+    pub const ALL: [Endpoint; 10] = [
+        Endpoint::AnimeSearch,
+        Endpoint::MangaSearch,
+        Endpoint::List,
+        Endpoint::AnimeAdd,
+        Endpoint::MangaAdd,
+        Endpoint::AnimeUpdate,
+        Endpoint::MangaUpdate,
+        Endpoint::AnimeDelete,
+        Endpoint::MangaDelete,
+        Endpoint::VerifyCredentials,
+    ];
+
+    /// Returns the path MyAnimeList uses for the endpoint.
+    pub fn default_path(&self) -> &'static str {
+        match *self {
+            Endpoint::AnimeSearch => "/api/anime/search.xml",
+            Endpoint::MangaSearch => "/api/manga/search.xml",
+            Endpoint::List => "/malappinfo.php",
+            Endpoint::AnimeAdd => "/api/animelist/add/{id}.xml",
+            Endpoint::MangaAdd => "/api/mangalist/add/{id}.xml",
+            Endpoint::AnimeUpdate => "/api/animelist/update/{id}.xml",
+            Endpoint::MangaUpdate => "/api/mangalist/update/{id}.xml",
+            Endpoint::AnimeDelete => "/api/animelist/delete/{id}.xml",
+            Endpoint::MangaDelete => "/api/mangalist/delete/{id}.xml",
+            Endpoint::VerifyCredentials => "/api/account/verify_credentials.xml",
+        }
+    }
+
+    /// Returns true if the path of the endpoint needs to contain the ID of a series.
+    pub fn takes_id(&self) -> bool {
+        match *self {
+            Endpoint::AnimeAdd
+            | Endpoint::MangaAdd
+            | Endpoint::AnimeUpdate
+            | Endpoint::MangaUpdate
+            | Endpoint::AnimeDelete
+            | Endpoint::MangaDelete => true,
+            _ => false,
+        }
+    }
+}
+
+/// Maps every [`Endpoint`] to the path requests for it are sent to.
+///
+/// [`Endpoint`]: ./enum.Endpoint.html
+///
+/// # Examples
+///
+/// ```
+/// use mal::endpoint::{Endpoint, Endpoints};
+///
+/// let mut endpoints = Endpoints::default();
+/// assert_eq!(endpoints.get(Endpoint::List), "/malappinfo.php");
+///
+/// endpoints.set(Endpoint::AnimeAdd, "/anime/{id}/add").unwrap();
+/// assert_eq!(endpoints.get(Endpoint::AnimeAdd), "/anime/{id}/add");
+///
+/// // Paths must be absolute, and only include the ID of the series when the endpoint needs it
+/// assert!(endpoints.set(Endpoint::List, "malappinfo.php").is_err());
+/// assert!(endpoints.set(Endpoint::AnimeDelete, "/anime/delete").is_err());
+/// assert!(endpoints.set(Endpoint::List, "/list/{id}").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoints {
+    paths: HashMap<Endpoint, String>,
+}
+
+impl Endpoints {
+    /// Returns the path of the specified endpoint.
+    #[inline]
+    pub fn get(&self, endpoint: Endpoint) -> &str {
+        self.paths
+            .get(&endpoint)
+            .map(String::as_str)
+            .unwrap_or_else(|| endpoint.default_path())
+    }
+
+    /// Sets the path of the specified endpoint.
+    ///
+    /// The path must start with a `/`. Endpoints that operate on a single series must contain
+    /// an `{id}` placeholder, and other endpoints must not contain one.
+    pub fn set<S: Into<String>>(&mut self, endpoint: Endpoint, path: S) -> Result<(), ConfigError> {
+        let path = path.into();
+
+        if !path.starts_with('/') || endpoint.takes_id() != path.contains(ID_PLACEHOLDER) {
+            return Err(ConfigError::InvalidEndpoint(endpoint, path));
+        }
+
+        self.paths.insert(endpoint, path);
+        Ok(())
+    }
+
+    /// Returns the full URL of the specified endpoint relative to `base_url`.
+    ///
+    /// The path of `base_url` is kept, so a base URL of `http://localhost/mal` will send
+    /// requests for the list endpoint to `http://localhost/mal/malappinfo.php`.
+    pub fn url(&self, base_url: &Url, endpoint: Endpoint, id: Option<u32>) -> Url {
+        let mut path = self.get(endpoint).to_string();
+
+        if let Some(id) = id {
+            path = path.replace(ID_PLACEHOLDER, &id.to_string());
+        }
+
+        join_path(base_url, &path)
+    }
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        let paths = Endpoint::ALL
+            .iter()
+            .map(|endpoint| (*endpoint, endpoint.default_path().to_string()))
+            .collect();
+
+        Endpoints { paths }
+    }
+}
+
+// Appends an absolute path to the path of a base URL instead of replacing it
+pub(crate) fn join_path(base_url: &Url, path: &str) -> Url {
+    let prefix = base_url.path();
+    let prefix = if prefix.ends_with('/') {
+        &prefix[..prefix.len() - 1]
+    } else {
+        prefix
+    };
+
+    let mut url = base_url.clone();
+    url.set_path(&format!("{}{}", prefix, path));
+    url
+}
+
+/// Parses and validates a base URL that requests to either version of the API are sent to.
+pub(crate) fn parse_base_url(base_url: &str) -> Result<Url, ConfigError> {
+    let url = Url::parse(base_url).map_err(|_| ConfigError::InvalidBaseUrl(base_url.into()))?;
+
+    match url.scheme() {
+        "http" | "https" if !url.cannot_be_a_base() && url.host().is_some() => Ok(url),
+        _ => Err(ConfigError::InvalidBaseUrl(base_url.into())),
+    }
+}
@@ -3,11 +3,20 @@ pub enum MALError {
     #[fail(display = "minidom error")]
     Minidom(#[cause] ::minidom::error::Error),
 
+    #[fail(display = "JSON error")]
+    Json(#[cause] ::serde_json::Error),
+
     #[fail(display = "request error")]
     Request(#[cause] RequestError),
 
     #[fail(display = "list error")]
     List(#[cause] ListError),
+
+    #[fail(display = "authorization error")]
+    Auth(#[cause] AuthError),
+
+    #[fail(display = "write queue error")]
+    Queue(#[cause] QueueError),
 }
 
 #[derive(Fail, Debug)]
@@ -20,6 +29,18 @@ pub enum RequestError {
 
     #[fail(display = "received bad response code from MAL: {}", _0)]
     BadResponseCode(::reqwest::StatusCode),
+
+    #[fail(display = "sending request would exceed the rate limit, retry in {:?}", _0)]
+    RateLimitExceeded(::std::time::Duration),
+
+    #[fail(display = "MAL is rate limiting requests, retry in {:?}", _0)]
+    RateLimited(::std::time::Duration),
+
+    #[fail(display = "MAL has banned the caller's IP, retry in {:?}", _0)]
+    Banned(::std::time::Duration),
+
+    #[fail(display = "failed to refresh the access token: {}", _0)]
+    TokenRefresh(Box<AuthError>),
 }
 
 #[derive(Fail, Debug)]
@@ -47,4 +68,150 @@ pub enum ListError {
 
     #[fail(display = "failed to parse XML node \"{}\" into appropriate type", _0)]
     XMLConversionFailed(String),
+
+    #[fail(display = "no JSON field named \"{}\"", _0)]
+    MissingJSONField(String),
+
+    #[fail(display = "failed to parse JSON field \"{}\" into appropriate type", _0)]
+    JSONConversionFailed(String),
+
+    #[fail(display = "list export has a type of {}, which does not match the requested list", _0)]
+    WrongExportType(u32),
+
+    #[fail(display = "gzipped list exports require the gzip feature")]
+    GzipDisabled,
+
+    #[cfg(feature = "csv")]
+    #[fail(display = "CSV error")]
+    Csv(#[cause] ::csv::Error),
+
+    #[fail(display = "no CSV column named \"{}\"", _0)]
+    MissingCSVColumn(String),
+
+    #[fail(display = "failed to parse CSV column \"{}\" into appropriate type", _0)]
+    CSVConversionFailed(String),
+
+    #[fail(display = "entry values failed validation")]
+    Validation(Vec<Violation>),
+}
+
+/// Represents a value that MyAnimeList would reject or change if it was sent.
+#[derive(Fail, Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// The score is higher than 10.
+    #[fail(display = "score of {} is not between 0 and 10", _0)]
+    ScoreOutOfRange(u8),
+
+    /// More episodes have been watched than the series has.
+    #[fail(display = "{} episodes watched, but the series only has {}", watched, total)]
+    TooManyEpisodes { watched: u32, total: u32 },
+
+    /// More chapters have been read than the series has.
+    #[fail(display = "{} chapters read, but the series only has {}", read, total)]
+    TooManyChapters { read: u32, total: u32 },
+
+    /// More volumes have been read than the series has.
+    #[fail(display = "{} volumes read, but the series only has {}", read, total)]
+    TooManyVolumes { read: u32, total: u32 },
+
+    /// The finish date is before the start date.
+    #[fail(display = "finish date of {} is before the start date of {}", finish, start)]
+    FinishBeforeStart {
+        start: ::chrono::NaiveDate,
+        finish: ::chrono::NaiveDate,
+    },
+}
+
+#[derive(Fail, Debug)]
+#[fail(display = "error on line {} of CSV file", line)]
+pub struct CsvRowError {
+    /// The line in the file the row starts on.
+    pub line: u64,
+    /// The error that occurred while parsing the row.
+    #[cause]
+    pub error: ListError,
+}
+
+#[derive(Fail, Debug, Clone, PartialEq)]
+pub enum QueryError {
+    #[fail(display = "unknown query field \"{}\"", _0)]
+    UnknownField(String),
+
+    #[fail(display = "\"{}\" is not a valid value for the query field \"{}\"", _1, _0)]
+    InvalidValue(String, String),
+
+    #[fail(display = "query field \"{}\" has no value", _0)]
+    MissingValue(String),
+
+    #[fail(display = "query contains an unterminated quote")]
+    UnterminatedQuote,
+}
+
+#[derive(Fail, Debug)]
+pub enum AuthError {
+    #[fail(display = "io error")]
+    Io(#[cause] ::std::io::Error),
+
+    #[fail(display = "JSON error")]
+    Json(#[cause] ::serde_json::Error),
+
+    #[fail(display = "error sending token request")]
+    Request(#[cause] RequestError),
+
+    #[fail(display = "authorization server rejected the request: {}", _0)]
+    Rejected(String),
+
+    #[fail(display = "the user denied the authorization request: {}", _0)]
+    AuthorizationDenied(String),
+
+    #[fail(display = "no JSON field named \"{}\" in token response", _0)]
+    MissingField(String),
+
+    #[fail(display = "no refresh token available")]
+    NoRefreshToken,
+
+    #[fail(display = "state sent back by the authorization server does not match")]
+    StateMismatch,
+
+    #[fail(display = "malformed authorization callback")]
+    BadCallback,
+
+    #[fail(display = "timed out waiting for the authorization callback")]
+    CallbackTimeout,
+}
+
+#[derive(Fail, Debug)]
+pub enum QueueError {
+    #[fail(display = "io error")]
+    Io(#[cause] ::std::io::Error),
+
+    #[fail(display = "JSON error")]
+    Json(#[cause] ::serde_json::Error),
+
+    #[fail(display = "list error")]
+    List(#[cause] ListError),
+
+    #[fail(display = "malformed record on line {} of the queue journal", _0)]
+    MalformedRecord(usize),
+}
+
+#[derive(Fail, Debug)]
+pub enum ConfigError {
+    #[fail(display = "\"{}\" is not a valid base URL", _0)]
+    InvalidBaseUrl(String),
+
+    #[fail(display = "\"{}\" is not a valid path for the {:?} endpoint", _1, _0)]
+    InvalidEndpoint(::endpoint::Endpoint, String),
+
+    #[fail(display = "\"{}\" is not a valid proxy URL", _0)]
+    InvalidProxy(String),
+
+    #[fail(display = "failed to parse root certificate")]
+    InvalidCertificate(#[cause] ::reqwest::Error),
+
+    #[fail(display = "failed to create HTTP client")]
+    BuildClient(#[cause] ::reqwest::Error),
+
+    #[fail(display = "HTTP client options can't be used with a custom transport")]
+    TransportConflict,
 }
@@ -8,8 +8,10 @@
 //! Please keep in mind that the API is rate limited to around ~5 requests per minute.
 //! If you send too many requests, the caller's IP will be banned for ~1-2 hours and all
 //! requests will return a 403 (Forbidden) status code.
+//! To help prevent this, all requests go through a [`RateLimiter`] by default.
 //!
 //! [`MAL`]: ./struct.MAL.html
+//! [`RateLimiter`]: ./rate_limit/struct.RateLimiter.html
 //! 
 //! # Examples
 //!
@@ -96,37 +98,173 @@ extern crate failure;
 #[macro_use]
 extern crate lazy_static;
 
+pub mod auth;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod endpoint;
 pub mod error;
 pub mod list;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+pub mod rate_limit;
+pub mod retry;
+pub mod queue;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transport;
 
 mod request;
+mod v2;
 
+extern crate base64;
 extern crate chrono;
+#[cfg(feature = "csv")]
+extern crate csv;
+#[cfg(feature = "gzip")]
+extern crate flate2;
+#[cfg(feature = "async")]
+extern crate futures;
 extern crate minidom;
+extern crate rand;
 extern crate reqwest;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[macro_use]
+extern crate serde_json;
+extern crate sha2;
+#[cfg(feature = "async")]
+extern crate tokio_core;
+extern crate url;
 
 #[cfg(feature = "anime")]
 use list::anime::AnimeEntry;
 #[cfg(feature = "manga")]
 use list::manga::MangaEntry;
 
-use error::{MALError, RequestError};
+use auth::OAuth;
+#[cfg(feature = "cache")]
+use cache::Cache;
+use endpoint::{Endpoint, Endpoints};
+use error::{ConfigError, MALError, RequestError};
 use list::{List, SeriesInfo};
+use queue::WriteQueue;
+use rate_limit::{BanTracker, RateLimiter};
+use retry::RetryPolicy;
 use request::Request;
-use reqwest::StatusCode;
+use reqwest::{StatusCode, Url};
 use std::borrow::Cow;
 use std::convert::Into;
 use std::fmt::{self, Debug};
+use std::sync::Arc;
+use std::time::Duration;
+use transport::{ClientOptions, ReqwestTransport, Transport};
+
+/// Represents the version of the MyAnimeList API that requests are sent to.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mal::{Api, MAL};
+///
+/// // Create a new MAL instance
+/// let mut mal = MAL::new("username", "");
+///
+/// // Send all requests to version 2 of the API
+/// mal.api = Api::V2 {
+///     client_id: "client id".into(),
+///     access_token: Some("access token".into()),
+/// };
+///
+/// // Read the user's anime list through version 2 of the API
+/// let list = mal.anime_list().read().unwrap();
+/// ```
+#[derive(Clone, PartialEq)]
+pub enum Api {
+    /// The original XML API, which authenticates with the user's name and password.
+    Legacy,
+    /// Version 2 of the API, which uses JSON and authenticates with OAuth2.
+    ///
+    /// If the [`MAL`] instance has an [`OAuth`] instance set, its access token is used instead
+    /// of `access_token`.
+    ///
+    /// [`MAL`]: ./struct.MAL.html
+    /// [`OAuth`]: ./auth/struct.OAuth.html
+    ///
+    /// Version 2 of the API does not provide any list statistics, so the user info of a list
+    /// is calculated from its entries instead. Because of this, the user's ID and the number
+    /// of days spent watching / reading will always be 0.
+    V2 {
+        /// The client ID of the application registered on MyAnimeList.
+        client_id: String,
+        /// The OAuth2 access token of the user.
+        /// If this isn't set, only public information can be read.
+        access_token: Option<String>,
+    },
+}
+
+impl Default for Api {
+    #[inline]
+    fn default() -> Self {
+        Api::Legacy
+    }
+}
+
+// Automatically deriving Debug will display the access token
+impl Debug for Api {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Api::Legacy => write!(f, "Legacy"),
+            Api::V2 { ref client_id, .. } => write!(f, "V2 {{ client_id: {:?} }}", client_id),
+        }
+    }
+}
 
 /// Used to interact with the MyAnimeList API with authorization being handled automatically.
 #[derive(Clone)]
 pub struct MAL<'a> {
     /// The user's name on MyAnimeList.
     pub username: String,
-    /// The user's password on MyAnimeList.
+    /// The user's password on MyAnimeList. This is only used by the legacy API.
     pub password: String,
-    /// The client used to send requests to the API.
-    pub client: Cow<'a, reqwest::Client>,
+    /// The version of the API that requests are sent to.
+    pub api: Api,
+    /// The URL that requests to the legacy API are sent to.
+    /// This is `https://myanimelist.net` by default.
+    pub base_url: Url,
+    /// The paths of every endpoint of the legacy API, relative to `base_url`.
+    pub endpoints: Endpoints,
+    /// The URL that requests to version 2 of the API are sent to.
+    /// This is `https://api.myanimelist.net` by default.
+    ///
+    /// The path of the URL is kept, so a URL of `http://localhost/mal` will send requests
+    /// for the user's info to `http://localhost/mal/v2/users/@me`.
+    pub v2_base_url: Url,
+    /// The transport used to send requests to the API.
+    ///
+    /// This replaces the `client` field of earlier versions. The reqwest client can still be
+    /// read with [`client`] and replaced with [`set_client`].
+    ///
+    /// [`client`]: #method.client
+    /// [`set_client`]: #method.set_client
+    pub transport: Arc<dyn Transport + 'a>,
+    /// The rate limiter every request will go through before being sent.
+    /// Setting this to `None` will disable rate limiting entirely.
+    pub rate_limiter: Option<RateLimiter>,
+    /// Keeps track of whether or not MyAnimeList has banned or rate limited the caller.
+    /// While a ban is being tracked, all requests will fail without being sent.
+    pub ban_tracker: BanTracker,
+    /// Determines how requests that fail because of transient errors are retried.
+    pub retry_policy: RetryPolicy,
+    /// Provides the access token for version 2 of the API and refreshes it before it expires.
+    pub oauth: Option<OAuth>,
+    /// The queue updates are journaled to when they can't be sent to MyAnimeList.
+    /// Failed updates are not queued when this is `None`, which is the default.
+    pub write_queue: Option<WriteQueue>,
+    /// The cache lists and search results are read from before sending a request.
+    /// Caching is disabled when this is `None`, which is the default.
+    #[cfg(feature = "cache")]
+    pub cache: Option<Cache>,
 }
 
 impl<'a> MAL<'a> {
@@ -154,14 +292,76 @@ impl<'a> MAL<'a> {
     pub fn with_client<S>(username: S, password: S, client: Cow<'a, reqwest::Client>) -> MAL<'a>
     where
         S: Into<String>,
+    {
+        MAL::with_transport(username, password, ReqwestTransport::new(client))
+    }
+
+    /// Creates a new instance of the MAL struct that will send all requests through the
+    /// specified [`Transport`]. This is mainly useful for testing, or for inspecting the
+    /// requests that are sent to the API.
+    ///
+    /// [`Transport`]: ./transport/trait.Transport.html
+    #[inline]
+    pub fn with_transport<S, T>(username: S, password: S, transport: T) -> MAL<'a>
+    where
+        S: Into<String>,
+        T: Transport + 'a,
     {
         MAL {
             username: username.into(),
             password: password.into(),
-            client,
+            api: Api::default(),
+            base_url: request::BASE_URL.clone(),
+            endpoints: Endpoints::default(),
+            v2_base_url: v2::BASE_URL.clone(),
+            transport: Arc::new(transport),
+            rate_limiter: Some(RateLimiter::default()),
+            ban_tracker: BanTracker::new(),
+            retry_policy: RetryPolicy::default(),
+            oauth: None,
+            write_queue: None,
+            #[cfg(feature = "cache")]
+            cache: None,
         }
     }
 
+    /// Creates a new instance of the MAL struct that will send requests to version 2 of the API
+    /// and authenticate them with the tokens of the specified [`OAuth`] instance.
+    ///
+    /// The access token will be refreshed automatically before it expires.
+    ///
+    /// [`OAuth`]: ./auth/struct.OAuth.html
+    pub fn with_oauth<S: Into<String>>(username: S, oauth: OAuth) -> MAL<'a> {
+        let mut mal = MAL::new(username.into(), String::new());
+
+        mal.api = Api::V2 {
+            client_id: oauth.client.client_id.clone(),
+            access_token: None,
+        };
+
+        mal.oauth = Some(oauth);
+        mal
+    }
+
+    /// Returns the reqwest client requests are sent with, or `None` if the [`transport`]
+    /// doesn't send requests with one.
+    ///
+    /// [`transport`]: #structfield.transport
+    #[deprecated(note = "use the `transport` field instead")]
+    #[inline]
+    pub fn client(&self) -> Option<&reqwest::Client> {
+        self.transport.reqwest_client()
+    }
+
+    /// Sends every request with the specified reqwest client, replacing the current [`transport`].
+    ///
+    /// [`transport`]: #structfield.transport
+    #[deprecated(note = "use the `transport` field instead")]
+    #[inline]
+    pub fn set_client(&mut self, client: Cow<'a, reqwest::Client>) {
+        self.transport = Arc::new(ReqwestTransport::new(client));
+    }
+
     /// Returns a new [`List`] instance to perform anime list operations.
     ///
     /// [`List`]: ./list/struct.List.html
@@ -211,8 +411,18 @@ impl<'a> Debug for MAL<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "MAL {{ username: {:?}, client: {:?} }}",
-            self.username, self.client
+            "MAL {{ username: {:?}, api: {:?}, base_url: {:?}, endpoints: {:?}, v2_base_url: {:?}, transport: {:?}, rate_limiter: {:?}, ban_tracker: {:?}, retry_policy: {:?}, oauth: {:?}, write_queue: {:?} }}",
+            self.username,
+            self.api,
+            self.base_url.as_str(),
+            self.endpoints,
+            self.v2_base_url.as_str(),
+            self.transport,
+            self.rate_limiter,
+            self.ban_tracker,
+            self.retry_policy,
+            self.oauth,
+            self.write_queue
         )
     }
 }
@@ -221,5 +431,277 @@ impl<'a> PartialEq for MAL<'a> {
     #[inline]
     fn eq(&self, other: &MAL<'a>) -> bool {
         self.username == other.username && self.password == other.password
+            && self.api == other.api && self.base_url == other.base_url
+            && self.endpoints == other.endpoints && self.v2_base_url == other.v2_base_url
+    }
+}
+
+/// Used to create a [`MAL`] instance with a custom configuration.
+///
+/// Unlike setting the fields of a [`MAL`] instance directly, the configuration is validated
+/// when [`build`] is called.
+///
+/// [`MAL`]: ./struct.MAL.html
+/// [`build`]: #method.build
+///
+/// # Examples
+///
+/// ```
+/// use mal::MALBuilder;
+/// use mal::endpoint::Endpoint;
+///
+/// // Send requests for the user's list through a caching proxy
+/// let mal = MALBuilder::new("username", "password")
+///     .base_url("http://localhost:8080/mal")
+///     .endpoint(Endpoint::List, "/list.php")
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(mal.base_url.as_str(), "http://localhost:8080/mal");
+/// assert_eq!(mal.endpoints.get(Endpoint::List), "/list.php");
+///
+/// // Send requests to version 2 of the API through the same proxy
+/// let mal = MALBuilder::new("username", "password")
+///     .v2_base_url("http://localhost:8080/mal-api")
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(mal.v2_base_url.as_str(), "http://localhost:8080/mal-api");
+///
+/// // Invalid configurations are rejected
+/// let result = MALBuilder::new("username", "password")
+///     .base_url("not a url")
+///     .build();
+///
+/// assert!(result.is_err());
+/// ```
+///
+/// Configuring the HTTP client that sends requests:
+///
+/// ```
+/// use mal::MALBuilder;
+/// use std::time::Duration;
+///
+/// let mal = MALBuilder::new("username", "password")
+///     .timeout(Duration::from_secs(30))
+///     .proxy("http://localhost:3128")
+///     .user_agent("my-tool/1.0")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct MALBuilder<'a> {
+    username: String,
+    password: String,
+    api: Api,
+    base_url: Option<String>,
+    endpoints: Vec<(Endpoint, String)>,
+    v2_base_url: Option<String>,
+    transport: Option<Arc<dyn Transport + 'a>>,
+    client_options: ClientOptions,
+    rate_limiter: Option<Option<RateLimiter>>,
+    retry_policy: RetryPolicy,
+    oauth: Option<OAuth>,
+    write_queue: Option<WriteQueue>,
+    #[cfg(feature = "cache")]
+    cache: Option<Cache>,
+}
+
+impl<'a> MALBuilder<'a> {
+    /// Creates a new `MALBuilder` with the specified user credentials and the default configuration.
+    #[inline]
+    pub fn new<S: Into<String>>(username: S, password: S) -> MALBuilder<'a> {
+        MALBuilder {
+            username: username.into(),
+            password: password.into(),
+            api: Api::default(),
+            base_url: None,
+            endpoints: Vec::new(),
+            v2_base_url: None,
+            transport: None,
+            client_options: ClientOptions::default(),
+            rate_limiter: None,
+            retry_policy: RetryPolicy::default(),
+            oauth: None,
+            write_queue: None,
+            #[cfg(feature = "cache")]
+            cache: None,
+        }
+    }
+
+    /// Sets the version of the API that requests are sent to.
+    #[inline]
+    pub fn api(mut self, api: Api) -> MALBuilder<'a> {
+        self.api = api;
+        self
+    }
+
+    /// Sets the URL that requests to the legacy API are sent to.
+    ///
+    /// The URL must use either the `http` or `https` scheme.
+    #[inline]
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> MALBuilder<'a> {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Sets the path of an endpoint of the legacy API, relative to the base URL.
+    ///
+    /// See [`Endpoints::set`] for the requirements of the path.
+    ///
+    /// [`Endpoints::set`]: ./endpoint/struct.Endpoints.html#method.set
+    #[inline]
+    pub fn endpoint<S: Into<String>>(mut self, endpoint: Endpoint, path: S) -> MALBuilder<'a> {
+        self.endpoints.push((endpoint, path.into()));
+        self
+    }
+
+    /// Sets the URL that requests to version 2 of the API are sent to.
+    ///
+    /// The URL must use either the `http` or `https` scheme.
+    #[inline]
+    pub fn v2_base_url<S: Into<String>>(mut self, v2_base_url: S) -> MALBuilder<'a> {
+        self.v2_base_url = Some(v2_base_url.into());
+        self
+    }
+
+    /// Sets the [`Transport`] used to send requests.
+    ///
+    /// A custom transport can't be combined with the options that configure the HTTP client,
+    /// such as [`timeout`] or [`proxy`].
+    ///
+    /// [`Transport`]: ./transport/trait.Transport.html
+    /// [`timeout`]: #method.timeout
+    /// [`proxy`]: #method.proxy
+    #[inline]
+    pub fn transport<T: Transport + 'a>(mut self, transport: T) -> MALBuilder<'a> {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Sets how long a request can take before it is aborted.
+    ///
+    /// The timeout covers both connecting to the server and reading its response.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> MALBuilder<'a> {
+        self.client_options.timeout = Some(timeout);
+        self
+    }
+
+    /// Sends all requests through the HTTP proxy at the specified URL.
+    #[inline]
+    pub fn proxy<S: Into<String>>(mut self, url: S) -> MALBuilder<'a> {
+        self.client_options.proxy = Some(url.into());
+        self
+    }
+
+    /// Sets the User-Agent header that is sent with every request.
+    #[inline]
+    pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> MALBuilder<'a> {
+        self.client_options.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Trusts the DER-encoded root certificate in addition to the system's root certificates.
+    ///
+    /// This can be called multiple times to trust several certificates.
+    #[inline]
+    pub fn root_certificate<B: Into<Vec<u8>>>(mut self, der: B) -> MALBuilder<'a> {
+        self.client_options.root_certificates.push(der.into());
+        self
+    }
+
+    /// Sets the rate limiter every request will go through.
+    /// Passing `None` will disable rate limiting entirely.
+    #[inline]
+    pub fn rate_limiter(mut self, rate_limiter: Option<RateLimiter>) -> MALBuilder<'a> {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Sets how requests that fail because of transient errors are retried.
+    #[inline]
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> MALBuilder<'a> {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sends requests to version 2 of the API and authenticates them with the tokens of the
+    /// specified [`OAuth`] instance.
+    ///
+    /// [`OAuth`]: ./auth/struct.OAuth.html
+    #[inline]
+    pub fn oauth(mut self, oauth: OAuth) -> MALBuilder<'a> {
+        self.api = Api::V2 {
+            client_id: oauth.client.client_id.clone(),
+            access_token: None,
+        };
+
+        self.oauth = Some(oauth);
+        self
+    }
+
+    /// Sets the queue updates are journaled to when they can't be sent to MyAnimeList.
+    #[inline]
+    pub fn write_queue(mut self, write_queue: WriteQueue) -> MALBuilder<'a> {
+        self.write_queue = Some(write_queue);
+        self
+    }
+
+    /// Sets the cache lists and search results are read from before sending a request.
+    #[cfg(feature = "cache")]
+    #[inline]
+    pub fn cache(mut self, cache: Cache) -> MALBuilder<'a> {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Validates the configuration and creates a new [`MAL`] instance from it.
+    ///
+    /// [`MAL`]: ./struct.MAL.html
+    pub fn build(self) -> Result<MAL<'a>, ConfigError> {
+        let base_url = match self.base_url {
+            Some(ref base_url) => endpoint::parse_base_url(base_url)?,
+            None => request::BASE_URL.clone(),
+        };
+
+        let v2_base_url = match self.v2_base_url {
+            Some(ref v2_base_url) => endpoint::parse_base_url(v2_base_url)?,
+            None => v2::BASE_URL.clone(),
+        };
+
+        let mut endpoints = Endpoints::default();
+
+        for (endpoint, path) in self.endpoints {
+            endpoints.set(endpoint, path)?;
+        }
+
+        let transport: Arc<dyn Transport + 'a> = match self.transport {
+            Some(_) if !self.client_options.is_default() => {
+                return Err(ConfigError::TransportConflict)
+            }
+            Some(transport) => transport,
+            None => {
+                let client = self.client_options.build()?;
+                Arc::new(ReqwestTransport::new(Cow::Owned(client)))
+            }
+        };
+
+        Ok(MAL {
+            username: self.username,
+            password: self.password,
+            api: self.api,
+            base_url,
+            endpoints,
+            v2_base_url,
+            transport,
+            rate_limiter: self.rate_limiter.unwrap_or_else(|| Some(RateLimiter::default())),
+            ban_tracker: BanTracker::new(),
+            retry_policy: self.retry_policy,
+            oauth: self.oauth,
+            write_queue: self.write_queue,
+            #[cfg(feature = "cache")]
+            cache: self.cache,
+        })
     }
 }
@@ -0,0 +1,226 @@
+//! This module allows the lists of two different users to be compared, similar to the
+//! shared series and affinity shown on MyAnimeList's profile comparison page.
+//!
+//! The comparison is represented by a [`ListComparison`], which contains the series both users
+//! have on their list, the series only one of them has, and how closely their scores agree.
+//!
+//! [`ListComparison`]: ./struct.ListComparison.html
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use mal::MAL;
+//!
+//! // Reading a user's list doesn't require their password
+//! let ours = MAL::new("username", "password").anime_list().read().unwrap();
+//! let theirs = MAL::new("other_username", "").anime_list().read().unwrap();
+//!
+//! let comparison = ours.compare(&theirs);
+//!
+//! if let Some(affinity) = comparison.affinity {
+//!     println!("affinity: {:.1}%", affinity * 100.0);
+//! }
+//!
+//! for shared in &comparison.shared {
+//!     if let Some(difference) = shared.score_difference() {
+//!         println!("{}: {:+}", shared.first.series_info.title, difference);
+//!     }
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use super::{ListEntries, ListEntry};
+
+/// Represents a series that is on both lists.
+#[derive(Debug, Clone)]
+pub struct SharedEntry<E: ListEntry> {
+    /// The entry from the first list.
+    pub first: E,
+    /// The entry from the second list.
+    pub second: E,
+}
+
+impl<E: ListEntry> SharedEntry<E> {
+    /// Returns the first list's score minus the second list's score.
+    ///
+    /// This is `None` unless the series has been scored on both lists.
+    #[inline]
+    pub fn score_difference(&self) -> Option<i16> {
+        match (self.first.summary().score, self.second.summary().score) {
+            (0, _) | (_, 0) => None,
+            (first, second) => Some(i16::from(first) - i16::from(second)),
+        }
+    }
+}
+
+/// Contains the result of comparing the lists of two users.
+///
+/// # Examples
+///
+/// ```
+/// use mal::list::ListEntries;
+/// use mal::list::anime::{AiringStatus, AnimeEntry, AnimeInfo, AnimeType, AnimeUserInfo};
+///
+/// # fn info(id: u32) -> AnimeInfo {
+/// #     AnimeInfo {
+/// #         id,
+/// #         title: "Toradora!".into(),
+/// #         english_title: None,
+/// #         synonyms: Vec::new(),
+/// #         episodes: 25,
+/// #         series_type: AnimeType::TV,
+/// #         airing_status: AiringStatus::FinishedAiring,
+/// #         start_date: None,
+/// #         end_date: None,
+/// #         synopsis: None,
+/// #         image_url: String::new(),
+/// #     }
+/// # }
+/// # fn user_info() -> AnimeUserInfo {
+/// #     AnimeUserInfo {
+/// #         user_id: 1,
+/// #         watching: 0,
+/// #         completed: 0,
+/// #         on_hold: 0,
+/// #         dropped: 0,
+/// #         plan_to_watch: 0,
+/// #         days_spent_watching: 0.0,
+/// #     }
+/// # }
+/// fn scored(id: u32, score: u8) -> AnimeEntry {
+///     let mut entry = AnimeEntry::new(info(id));
+///     entry.values.set_score(score);
+///     entry
+/// }
+///
+/// let ours = ListEntries {
+///     user_info: user_info(),
+///     entries: vec![scored(1, 9), scored(2, 6), scored(3, 4), scored(4, 8)],
+/// };
+///
+/// let theirs = ListEntries {
+///     user_info: user_info(),
+///     entries: vec![scored(1, 10), scored(2, 7), scored(3, 5), scored(5, 8)],
+/// };
+///
+/// let comparison = ours.compare(&theirs);
+///
+/// assert_eq!(comparison.shared.len(), 3);
+/// assert_eq!(comparison.shared[0].score_difference(), Some(-1));
+/// assert_eq!(comparison.only_first[0].series_info.id, 4);
+/// assert_eq!(comparison.only_second[0].series_info.id, 5);
+///
+/// // Both users rank the shared series the same way
+/// assert!(comparison.affinity.unwrap() > 0.99);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ListComparison<E: ListEntry> {
+    /// Series that are on both lists, in the order of the first list.
+    pub shared: Vec<SharedEntry<E>>,
+    /// Series that are only on the first list.
+    pub only_first: Vec<E>,
+    /// Series that are only on the second list.
+    pub only_second: Vec<E>,
+    /// The Pearson correlation between the scores of series that were scored on both lists,
+    /// from -1.0 to 1.0. MyAnimeList displays this as a percentage.
+    ///
+    /// This is `None` if fewer than two series were scored on both lists, or if every score
+    /// on either list is the same, since a correlation can't be computed in those cases.
+    pub affinity: Option<f32>,
+}
+
+impl<E: ListEntry> ListComparison<E> {
+    /// Returns the mean of the score differences of every series that was scored on both lists,
+    /// as returned by [`SharedEntry::score_difference`].
+    ///
+    /// [`SharedEntry::score_difference`]: ./struct.SharedEntry.html#method.score_difference
+    pub fn mean_score_difference(&self) -> Option<f32> {
+        let differences = self.shared
+            .iter()
+            .filter_map(SharedEntry::score_difference)
+            .collect::<Vec<_>>();
+
+        if differences.is_empty() {
+            return None;
+        }
+
+        let sum = differences.iter().map(|&diff| f32::from(diff)).sum::<f32>();
+        Some(sum / differences.len() as f32)
+    }
+}
+
+impl<E: ListEntry + Clone> ListEntries<E> {
+    /// Compares the entries of this list with the list of another user.
+    ///
+    /// Entries are matched by the ID of their series.
+    pub fn compare(&self, other: &ListEntries<E>) -> ListComparison<E> {
+        let other_entries = other.entries
+            .iter()
+            .map(|entry| (entry.id(), entry))
+            .collect::<HashMap<_, _>>();
+
+        let ids = self.entries
+            .iter()
+            .map(ListEntry::id)
+            .collect::<HashSet<_>>();
+
+        let mut comparison = ListComparison {
+            shared: Vec::new(),
+            only_first: Vec::new(),
+            only_second: Vec::new(),
+            affinity: None,
+        };
+
+        for entry in &self.entries {
+            match other_entries.get(&entry.id()) {
+                Some(&other) => comparison.shared.push(SharedEntry {
+                    first: entry.clone(),
+                    second: other.clone(),
+                }),
+                None => comparison.only_first.push(entry.clone()),
+            }
+        }
+
+        comparison.only_second = other.entries
+            .iter()
+            .filter(|entry| !ids.contains(&entry.id()))
+            .cloned()
+            .collect();
+
+        let scores = comparison
+            .shared
+            .iter()
+            .map(|shared| (shared.first.summary().score, shared.second.summary().score))
+            .filter(|&(first, second)| first > 0 && second > 0)
+            .collect::<Vec<_>>();
+
+        comparison.affinity = pearson(&scores);
+        comparison
+    }
+}
+
+fn pearson(scores: &[(u8, u8)]) -> Option<f32> {
+    if scores.len() < 2 {
+        return None;
+    }
+
+    let len = scores.len() as f64;
+    let mean_x = scores.iter().map(|&(x, _)| f64::from(x)).sum::<f64>() / len;
+    let mean_y = scores.iter().map(|&(_, y)| f64::from(y)).sum::<f64>() / len;
+
+    let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+
+    for &(x, y) in scores {
+        let (dx, dy) = (f64::from(x) - mean_x, f64::from(y) - mean_y);
+
+        covariance += dx * dy;
+        variance_x += dx * dx;
+        variance_y += dy * dy;
+    }
+
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return None;
+    }
+
+    Some((covariance / (variance_x * variance_y).sqrt()) as f32)
+}
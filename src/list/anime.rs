@@ -1,22 +1,35 @@
 //! Contains the required data structures to search for anime on MyAnimeList and
 //! perform operations on a user's anime list.
 
-use chrono::{DateTime, NaiveDate, TimeZone, Utc};
-use error::ListError;
-use list::{self, Status};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use error::{ListError, Violation};
+use list::{self, EntrySummary, Priority, ReleaseStatus, RepeatValue, Status};
+#[cfg(feature = "csv")]
+use list::csv::CsvRow;
+#[cfg(feature = "cache")]
+use list::ListEntries;
+use list::diff::FieldChange;
+use list::export::{self, text_elem};
+use list::stats::StatusCounts;
 use minidom::Element;
 use request::ListType;
+#[cfg(feature = "cache")]
+use serde_json;
+use serde_json::Value;
 use SeriesInfo;
+use v2;
 use super::{ChangeTracker, EntryValues, ListEntry, UserInfo};
 
 /// Represents basic information of an anime series on MyAnimeList.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnimeInfo {
     /// The ID of the series.
     pub id: u32,
     /// The Japanese title of the series.
     pub title: String,
-    /// The English title of the series. It is not available on list entries.
+    /// The English title of the series.
+    /// It is not available on list entries when using the legacy API.
     pub english_title: Option<String>,
     /// The alternative titles for the series.
     pub synonyms: Vec<String>,
@@ -27,8 +40,10 @@ pub struct AnimeInfo {
     /// The current airing status of the series.
     pub airing_status: AiringStatus,
     /// The date the series started airing.
+    #[cfg_attr(feature = "serde", serde(with = "::list::serialize::date"))]
     pub start_date: Option<NaiveDate>,
     /// The date the series finished airing.
+    #[cfg_attr(feature = "serde", serde(with = "::list::serialize::date"))]
     pub end_date: Option<NaiveDate>,
     /// The description of the series.
     /// It is not available on list entries, and it is formatted as HTML.
@@ -68,6 +83,47 @@ impl SeriesInfo for AnimeInfo {
 
         Ok(entry)
     }
+
+    #[doc(hidden)]
+    fn from_json(json: &Value) -> Result<AnimeInfo, ListError> {
+        let entry = AnimeInfo {
+            id: v2::parse_u32(json, "id")?,
+            title: v2::parse_string(json, "title")?,
+            english_title: v2::english_title(json),
+            synonyms: v2::synonyms(json),
+            episodes: v2::parse_u32(json, "num_episodes").unwrap_or(0),
+            series_type: {
+                let s_type = v2::parse_string(json, "media_type")?;
+
+                match s_type.as_str() {
+                    "tv_special" => AnimeType::Special,
+                    "unknown" | "pv" | "cm" => AnimeType::Unknown,
+                    _ => AnimeType::from_str(&s_type)
+                        .ok_or_else(|| ListError::UnknownSeriesType(s_type))?,
+                }
+            },
+            airing_status: {
+                let status = v2::parse_string(json, "status")?;
+
+                AiringStatus::from_str(&status.replace('_', " "))
+                    .ok_or_else(|| ListError::UnknownStatus(status))?
+            },
+            start_date: v2::parse_date(json, "start_date"),
+            end_date: v2::parse_date(json, "end_date"),
+            synopsis: v2::parse_string(json, "synopsis").ok(),
+            image_url: v2::image_url(json),
+        };
+
+        Ok(entry)
+    }
+
+    #[doc(hidden)]
+    fn titles(&self) -> Vec<&str> {
+        let mut titles = vec![self.title.as_str()];
+        titles.extend(self.english_title.as_ref().map(String::as_str));
+        titles.extend(self.synonyms.iter().map(String::as_str));
+        titles
+    }
 }
 
 impl PartialEq for AnimeInfo {
@@ -111,12 +167,67 @@ gen_list_field_enum!(AiringStatus,
     NotYetAired = [3, "not yet aired"],
 );
 
+gen_list_field_enum!(StorageType,
+    ["The user hasn't set a storage type."]
+    NotSet = [0, ""],
+
+    ["The series is stored on a hard drive."]
+    HardDrive = [1, "hard drive"],
+
+    ["The series is stored on a DVD or CD."]
+    DVD = [2, "dvd / cd"],
+
+    ["The series isn't stored anywhere."]
+    NotStored = [3, "none"],
+
+    ["The series is stored on a retail DVD."]
+    RetailDVD = [4, "retail dvd"],
+
+    ["The series is stored on a VHS tape."]
+    VHS = [5, "vhs"],
+
+    ["The series is stored on an external hard drive."]
+    ExternalHD = [6, "external hd"],
+
+    ["The series is stored on network-attached storage."]
+    NAS = [7, "nas"],
+
+    ["The series is stored on a Blu-ray disc."]
+    BluRay = [8, "blu-ray"],
+);
+
+impl Default for StorageType {
+    #[inline]
+    fn default() -> Self {
+        StorageType::NotSet
+    }
+}
+
+impl StorageType {
+    // The name MyAnimeList uses for the storage type in list exports
+    fn export_name(&self) -> &'static str {
+        match *self {
+            StorageType::NotSet => "",
+            StorageType::HardDrive => "Hard Drive",
+            StorageType::DVD => "DVD / CD",
+            StorageType::NotStored => "None",
+            StorageType::RetailDVD => "Retail DVD",
+            StorageType::VHS => "VHS",
+            StorageType::ExternalHD => "External HD",
+            StorageType::NAS => "NAS",
+            StorageType::BluRay => "Blu-ray",
+        }
+    }
+}
+
 /// Contains information about an anime series on a user's list.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnimeEntry {
     /// The general series information.
     pub series_info: AnimeInfo,
     /// The last time the series was updated.
+    #[cfg_attr(feature = "serde", serde(with = "::list::serialize::datetime"))]
     pub last_updated_time: DateTime<Utc>,
     /// Contains values that can be set / updated on a user's list.
     pub values: AnimeValues,
@@ -154,12 +265,133 @@ impl AnimeEntry {
             values: AnimeValues::new(),
         }
     }
+
+    /// Sets the number of watched episodes and updates the entry's status and dates like
+    /// the MyAnimeList website does, using today's date.
+    ///
+    /// See [`set_progress_on`] for the rules that are applied.
+    ///
+    /// [`set_progress_on`]: #method.set_progress_on
+    #[inline]
+    pub fn set_progress(&mut self, watched: u32) -> &mut AnimeEntry {
+        self.set_progress_on(watched, Local::today().naive_local())
+    }
+
+    /// Sets the number of watched episodes and updates the entry's status and dates like
+    /// the MyAnimeList website does:
+    ///
+    /// * Once an episode has been watched, a series that was planned to be watched is set
+    ///   to watching, and the start date is set to `date` if it hasn't been set yet.
+    /// * Once every episode has been watched, the series is set to completed, and the finish date
+    ///   is set to `date` if it hasn't been set yet. If the series was being rewatched,
+    ///   the rewatch is finished and the number of times it was rewatched is incremented.
+    ///
+    /// The last episode can only be detected when the series' number of episodes is known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate chrono;
+    /// # extern crate mal;
+    /// use chrono::NaiveDate;
+    /// use mal::list::Status;
+    /// use mal::list::anime::{AiringStatus, AnimeEntry, AnimeInfo, AnimeType};
+    ///
+    /// # fn main() {
+    /// let info = AnimeInfo {
+    ///     id: 4224,
+    ///     title: "Toradora!".into(),
+    ///     english_title: None,
+    ///     synonyms: Vec::new(),
+    ///     episodes: 25,
+    ///     series_type: AnimeType::TV,
+    ///     airing_status: AiringStatus::FinishedAiring,
+    ///     start_date: None,
+    ///     end_date: None,
+    ///     synopsis: None,
+    ///     image_url: String::new(),
+    /// };
+    ///
+    /// let mut entry = AnimeEntry::new(info);
+    /// let start = NaiveDate::from_ymd(2018, 4, 1);
+    /// let finish = NaiveDate::from_ymd(2018, 4, 8);
+    ///
+    /// entry.set_progress_on(1, start);
+    /// assert_eq!(entry.values.status(), Status::WatchingOrReading);
+    /// assert_eq!(entry.values.start_date(), Some(start));
+    ///
+    /// entry.set_progress_on(25, finish);
+    /// assert_eq!(entry.values.status(), Status::Completed);
+    /// assert_eq!(entry.values.finish_date(), Some(finish));
+    ///
+    /// // Finishing a rewatch doesn't change the dates
+    /// entry.values.set_rewatching(true);
+    /// entry.set_progress_on(25, NaiveDate::from_ymd(2018, 6, 1));
+    /// assert_eq!(entry.values.rewatching(), false);
+    /// assert_eq!(entry.values.times_rewatched(), 1);
+    /// assert_eq!(entry.values.finish_date(), Some(finish));
+    /// # }
+    /// ```
+    pub fn set_progress_on(&mut self, watched: u32, date: NaiveDate) -> &mut AnimeEntry {
+        {
+            let values = &mut self.values;
+            values.set_watched_episodes(watched);
+
+            if watched > 0 {
+                if values.status() == Status::PlanToWatchOrRead {
+                    values.set_status(Status::WatchingOrReading);
+                }
+
+                if values.start_date().is_none() {
+                    values.set_start_date(Some(date));
+                }
+            }
+
+            let total = self.series_info.episodes;
+
+            if total > 0 && watched >= total {
+                if values.rewatching() {
+                    let times_rewatched = values.times_rewatched();
+
+                    values
+                        .set_rewatching(false)
+                        .set_times_rewatched(times_rewatched + 1);
+                }
+
+                if values.status() != Status::Completed {
+                    values.set_status(Status::Completed);
+                }
+
+                if values.finish_date().is_none() {
+                    values.set_finish_date(Some(date));
+                }
+            }
+        }
+
+        self
+    }
 }
 
 impl ListEntry for AnimeEntry {
     type Info = AnimeInfo;
     type Values = AnimeValues;
     type UserInfo = AnimeUserInfo;
+    type SeriesType = AnimeType;
+
+    #[doc(hidden)]
+    fn violations(&self) -> Vec<Violation> {
+        let mut violations = self.values.violations();
+        let watched = &self.values.watched_episodes;
+
+        if list::exceeds_total(watched, self.series_info.episodes) {
+            violations.push(Violation::TooManyEpisodes {
+                watched: watched.value,
+                total: self.series_info.episodes,
+            });
+        }
+
+        violations
+    }
 
     #[doc(hidden)]
     fn from_xml(xml: &Element) -> Result<AnimeEntry, ListError> {
@@ -203,12 +435,338 @@ impl ListEntry for AnimeEntry {
         Ok(entry)
     }
 
+    #[doc(hidden)]
+    fn from_json(json: &Value) -> Result<AnimeEntry, ListError> {
+        let list_status = v2::field(json, "list_status")?;
+
+        let entry = AnimeEntry {
+            series_info: AnimeInfo::from_json(v2::field(json, "node")?)?,
+            last_updated_time: v2::parse_time(list_status, "updated_at")?,
+            values: AnimeValues::from_json(list_status)?,
+        };
+
+        Ok(entry)
+    }
+
+    #[doc(hidden)]
+    fn from_export_xml(xml: &Element) -> Result<AnimeEntry, ListError> {
+        let info = AnimeInfo {
+            id: list::parse_xml_child(xml, "series_animedb_id")?,
+            title: list::parse_xml_child(xml, "series_title")?,
+            english_title: None,
+            synonyms: Vec::new(),
+            episodes: list::parse_xml_child_or_default(xml, "series_episodes")?,
+            series_type: {
+                let s_type = list::parse_xml_child_or_default::<String>(xml, "series_type")?;
+                AnimeType::from_str(&s_type).unwrap_or(AnimeType::Unknown)
+            },
+            // The export format doesn't include the airing status of a series
+            airing_status: AiringStatus::FinishedAiring,
+            start_date: None,
+            end_date: None,
+            synopsis: None,
+            image_url: String::new(),
+        };
+
+        let values = AnimeValues {
+            watched_episodes: list::parse_xml_child::<u32>(xml, "my_watched_episodes")?.into(),
+            start_date: {
+                list::parse_str_date(&list::parse_xml_child::<String>(xml, "my_start_date")?).into()
+            },
+            finish_date: {
+                list::parse_str_date(&list::parse_xml_child::<String>(xml, "my_finish_date")?)
+                    .into()
+            },
+            status: export::parse_status(xml, "my_status")?.into(),
+            score: list::parse_xml_child::<u8>(xml, "my_score")?.into(),
+            rewatching: {
+                list::parse_xml_child_or_default::<u8>(xml, "my_rewatching")
+                    .map(|v| v == 1)?
+                    .into()
+            },
+            tags: export::parse_tags(xml, "my_tags")?.into(),
+            storage_type: {
+                let storage = list::parse_xml_child_or_default::<String>(xml, "my_storage")?;
+                StorageType::from_str(&storage).unwrap_or_default().into()
+            },
+            storage_value: list::parse_xml_child_or_default::<f32>(xml, "my_storage_value")?.into(),
+            times_rewatched: {
+                list::parse_xml_child_or_default::<u32>(xml, "my_times_watched")?.into()
+            },
+            rewatch_value: {
+                let value = list::parse_xml_child_or_default::<String>(xml, "my_rewatch_value")?;
+                RepeatValue::from_str(&value).unwrap_or_default().into()
+            },
+            priority: {
+                let priority = list::parse_xml_child_or_default::<String>(xml, "my_priority")?;
+                Priority::from_str(&priority).unwrap_or_default().into()
+            },
+            enable_discussion: {
+                list::parse_xml_child_or_default::<u8>(xml, "my_discuss")
+                    .map(|v| v == 1)?
+                    .into()
+            },
+            comments: list::parse_xml_child_or_default::<String>(xml, "my_comments")?.into(),
+            downloaded_episodes: 0.into(),
+        };
+
+        let entry = AnimeEntry {
+            series_info: info,
+            last_updated_time: Utc::now(),
+            values,
+        };
+
+        Ok(entry)
+    }
+
+    #[doc(hidden)]
+    fn to_export_xml(&self) -> Element {
+        let info = &self.series_info;
+        let values = &self.values;
+
+        let series_type = match info.series_type {
+            AnimeType::Unknown => "Unknown".into(),
+            AnimeType::TV => "TV".into(),
+            AnimeType::OVA => "OVA".into(),
+            AnimeType::ONA => "ONA".into(),
+            other => export::title_case(other.as_str()),
+        };
+
+        let mut elem = Element::bare("anime");
+        elem.append_child(text_elem("series_animedb_id", info.id));
+        elem.append_child(text_elem("series_title", &info.title));
+        elem.append_child(text_elem("series_type", series_type));
+        elem.append_child(text_elem("series_episodes", info.episodes));
+        elem.append_child(text_elem("my_id", 0));
+        elem.append_child(text_elem("my_watched_episodes", values.watched_episodes()));
+        elem.append_child(text_elem("my_start_date", export::date_to_str(values.start_date())));
+        elem.append_child(text_elem("my_finish_date", export::date_to_str(values.finish_date())));
+        elem.append_child(text_elem("my_score", values.score()));
+        elem.append_child(text_elem("my_storage", values.storage_type().export_name()));
+        elem.append_child(text_elem("my_storage_value", format!("{:.2}", values.storage_value())));
+        elem.append_child(text_elem(
+            "my_status",
+            export::status_to_str(values.status(), ListType::Anime),
+        ));
+        elem.append_child(text_elem("my_comments", values.comments()));
+        elem.append_child(text_elem("my_times_watched", values.times_rewatched()));
+        elem.append_child(text_elem(
+            "my_rewatch_value",
+            export::title_case(values.rewatch_value().as_str()),
+        ));
+        elem.append_child(text_elem("my_priority", values.priority().as_str().to_uppercase()));
+        elem.append_child(text_elem("my_tags", values.tags().join(", ")));
+        elem.append_child(text_elem("my_rewatching", values.rewatching() as u8));
+        elem.append_child(text_elem("my_rewatching_ep", 0));
+        elem.append_child(text_elem("my_discuss", values.enable_discussion() as u8));
+        elem.append_child(text_elem("my_sns", "default"));
+        elem
+    }
+
+    #[cfg(feature = "csv")]
+    #[doc(hidden)]
+    fn to_csv_row(&self) -> CsvRow {
+        CsvRow {
+            id: self.series_info.id,
+            title: self.series_info.title.clone(),
+            series_type: self.series_info.series_type.as_str().into(),
+            status: Some(self.values.status()),
+            progress: Some(self.values.watched_episodes()),
+            score: Some(self.values.score()),
+            start_date: self.values.start_date().map(Some),
+            finish_date: self.values.finish_date().map(Some),
+            tags: Some(self.values.tags().clone()),
+            rewatching: Some(self.values.rewatching()),
+        }
+    }
+
+    #[cfg(feature = "csv")]
+    #[doc(hidden)]
+    fn values_from_csv_row(row: &CsvRow) -> AnimeValues {
+        let mut values = AnimeValues::new();
+
+        if let Some(status) = row.status {
+            values.set_status(status);
+        }
+
+        if let Some(episodes) = row.progress {
+            values.set_watched_episodes(episodes);
+        }
+
+        if let Some(score) = row.score {
+            values.set_score(score);
+        }
+
+        if let Some(start_date) = row.start_date {
+            values.set_start_date(start_date);
+        }
+
+        if let Some(finish_date) = row.finish_date {
+            values.set_finish_date(finish_date);
+        }
+
+        if let Some(ref tags) = row.tags {
+            *values.tags_mut() = tags.clone();
+        }
+
+        if let Some(rewatching) = row.rewatching {
+            values.set_rewatching(rewatching);
+        }
+
+        values
+    }
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    #[inline]
+    fn list_to_json(list: &ListEntries<AnimeEntry>) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(list)
+    }
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    #[inline]
+    fn list_from_json(json: Value) -> Result<ListEntries<AnimeEntry>, serde_json::Error> {
+        serde_json::from_value(json)
+    }
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    #[inline]
+    fn entry_to_json(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    #[inline]
+    fn search_results_to_json(results: &[AnimeInfo]) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(results)
+    }
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    #[inline]
+    fn search_results_from_json(json: Value) -> Result<Vec<AnimeInfo>, serde_json::Error> {
+        serde_json::from_value(json)
+    }
+
+    #[doc(hidden)]
+    fn user_info_from_entries(entries: &[AnimeEntry]) -> AnimeUserInfo {
+        let count = |status| entries.iter().filter(|e| e.values.status() == status).count() as u32;
+
+        AnimeUserInfo {
+            user_id: 0,
+            watching: count(Status::WatchingOrReading),
+            completed: count(Status::Completed),
+            on_hold: count(Status::OnHold),
+            dropped: count(Status::Dropped),
+            plan_to_watch: count(Status::PlanToWatchOrRead),
+            days_spent_watching: 0.0,
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn values(&self) -> &AnimeValues {
+        &self.values
+    }
+
     #[doc(hidden)]
     #[inline]
     fn values_mut(&mut self) -> &mut AnimeValues {
         &mut self.values
     }
 
+    #[doc(hidden)]
+    #[inline]
+    fn title(&self) -> &str {
+        &self.series_info.title
+    }
+
+    #[doc(hidden)]
+    fn summary(&self) -> EntrySummary {
+        let info = &self.series_info;
+
+        EntrySummary {
+            id: info.id,
+            title: &info.title,
+            english_title: info.english_title.as_ref().map(String::as_str),
+            synonyms: &info.synonyms,
+            series_type: info.series_type.as_str(),
+            release_status: match info.airing_status {
+                AiringStatus::Airing => ReleaseStatus::Ongoing,
+                AiringStatus::FinishedAiring => ReleaseStatus::Finished,
+                AiringStatus::NotYetAired => ReleaseStatus::Upcoming,
+            },
+            status: self.values.status(),
+            score: self.values.score(),
+            progress: self.values.watched_episodes(),
+            total: info.episodes,
+            tags: self.values.tags(),
+            start_date: self.values.start_date(),
+            finish_date: self.values.finish_date(),
+            last_updated_time: self.last_updated_time,
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn series_type(&self) -> AnimeType {
+        self.series_info.series_type
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn last_updated_time(&self) -> DateTime<Utc> {
+        self.last_updated_time
+    }
+
+    #[doc(hidden)]
+    fn field_changes(old: &AnimeEntry, new: &AnimeEntry) -> Vec<FieldChange> {
+        let (old, new) = (&old.values, &new.values);
+
+        let changes = vec![
+            FieldChange::between(old.status(), new.status(), FieldChange::Status),
+            FieldChange::between(
+                old.watched_episodes(),
+                new.watched_episodes(),
+                FieldChange::Progress,
+            ),
+            FieldChange::between(old.score(), new.score(), FieldChange::Score),
+            FieldChange::between(old.start_date(), new.start_date(), FieldChange::StartDate),
+            FieldChange::between(old.finish_date(), new.finish_date(), FieldChange::FinishDate),
+            FieldChange::between(old.tags().clone(), new.tags().clone(), FieldChange::Tags),
+        ];
+
+        changes.into_iter().flatten().collect()
+    }
+
+    #[doc(hidden)]
+    fn apply_change(values: &mut AnimeValues, change: &FieldChange) {
+        match *change {
+            FieldChange::Status(_, status) => {
+                values.set_status(status);
+            }
+            FieldChange::Progress(_, progress) => {
+                values.set_watched_episodes(progress);
+            }
+            FieldChange::Volumes(..) => (),
+            FieldChange::Score(_, score) => {
+                values.set_score(score);
+            }
+            FieldChange::StartDate(_, date) => {
+                values.set_start_date(date);
+            }
+            FieldChange::FinishDate(_, date) => {
+                values.set_finish_date(date);
+            }
+            FieldChange::Tags(_, ref tags) => {
+                *values.tags_mut() = tags.clone();
+            }
+        }
+    }
+
     #[doc(hidden)]
     #[inline]
     fn set_last_updated_time(&mut self) {
@@ -254,14 +812,25 @@ impl PartialEq for AnimeEntry {
 /// assert_eq!(values.score(), 7);
 /// ```
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnimeValues {
     watched_episodes: ChangeTracker<u32>,
+    #[cfg_attr(feature = "serde", serde(with = "::list::serialize::tracked_date"))]
     start_date: ChangeTracker<Option<NaiveDate>>,
+    #[cfg_attr(feature = "serde", serde(with = "::list::serialize::tracked_date"))]
     finish_date: ChangeTracker<Option<NaiveDate>>,
     status: ChangeTracker<Status>,
     score: ChangeTracker<u8>,
     rewatching: ChangeTracker<bool>,
     tags: ChangeTracker<Vec<String>>,
+    storage_type: ChangeTracker<StorageType>,
+    storage_value: ChangeTracker<f32>,
+    times_rewatched: ChangeTracker<u32>,
+    rewatch_value: ChangeTracker<RepeatValue>,
+    priority: ChangeTracker<Priority>,
+    enable_discussion: ChangeTracker<bool>,
+    comments: ChangeTracker<String>,
+    downloaded_episodes: ChangeTracker<u32>,
 }
 
 impl AnimeValues {
@@ -299,6 +868,56 @@ impl AnimeValues {
             tags: {
                 list::split_by_delim(&list::parse_xml_child::<String>(xml, "my_tags")?, ",").into()
             },
+            storage_type: {
+                let storage = list::parse_xml_child_or_default(xml, "my_storage_type")?;
+                StorageType::from_i32(storage).unwrap_or_default().into()
+            },
+            storage_value: list::parse_xml_child_or_default::<f32>(xml, "my_storage_value")?.into(),
+            times_rewatched: {
+                list::parse_xml_child_or_default::<u32>(xml, "my_times_rewatched")?.into()
+            },
+            rewatch_value: {
+                let value = list::parse_xml_child_or_default(xml, "my_rewatch_value")?;
+                RepeatValue::from_i32(value).unwrap_or_default().into()
+            },
+            priority: {
+                let priority = list::parse_xml_child_or_default(xml, "my_priority")?;
+                Priority::from_i32(priority).unwrap_or_default().into()
+            },
+            enable_discussion: {
+                list::parse_xml_child_or_default::<u8>(xml, "my_enable_discussion")
+                    .map(|v| v == 1)?
+                    .into()
+            },
+            comments: list::parse_xml_child_or_default::<String>(xml, "my_comments")?.into(),
+            downloaded_episodes: {
+                list::parse_xml_child_or_default::<u32>(xml, "my_downloaded_episodes")?.into()
+            },
+        };
+
+        Ok(values)
+    }
+
+    fn from_json(json: &Value) -> Result<AnimeValues, ListError> {
+        let values = AnimeValues {
+            watched_episodes: v2::parse_u32(json, "num_episodes_watched")?.into(),
+            start_date: v2::parse_date(json, "start_date").into(),
+            finish_date: v2::parse_date(json, "finish_date").into(),
+            status: v2::parse_status(json, "status")?.into(),
+            score: (v2::parse_u32(json, "score")? as u8).into(),
+            rewatching: v2::parse_bool(json, "is_rewatching").unwrap_or(false).into(),
+            tags: v2::parse_strings(json, "tags").into(),
+            times_rewatched: v2::parse_u32(json, "num_times_rewatched").unwrap_or(0).into(),
+            rewatch_value: {
+                let value = v2::parse_u32(json, "rewatch_value").unwrap_or(0);
+                RepeatValue::from_i32(value as i32).unwrap_or_default().into()
+            },
+            priority: {
+                let priority = v2::parse_u32(json, "priority").unwrap_or(0);
+                Priority::from_i32(priority as i32).unwrap_or_default().into()
+            },
+            comments: v2::parse_string(json, "comments").unwrap_or_default().into(),
+            ..AnimeValues::default()
         };
 
         Ok(values)
@@ -318,6 +937,19 @@ impl AnimeValues {
         self.tags.changed = true;
         &mut self.tags.value
     }
+
+    /// Returns the comments the user has written about the anime.
+    #[inline]
+    pub fn comments(&self) -> &str {
+        &self.comments.value
+    }
+
+    /// Sets the comments the user has written about the anime.
+    #[inline]
+    pub fn set_comments<S: Into<String>>(&mut self, comments: S) -> &mut AnimeValues {
+        self.comments.set(comments.into());
+        self
+    }
 }
 
 impl_tracker_getset!(AnimeValues,
@@ -327,20 +959,47 @@ impl_tracker_getset!(AnimeValues,
     [status, set_status, "current watch status of the series"]: Status,
     [score, set_score, "user's rating of the series"]: u8,
     [rewatching, set_rewatching, "current rewatch status of the series"]: bool,
+    [storage_type, set_storage_type, "type of storage the series is kept on"]: StorageType,
+    [storage_value, set_storage_value, "amount of storage the series takes up"]: f32,
+    [times_rewatched, set_times_rewatched, "number of times the user has rewatched the series"]: u32,
+    [rewatch_value, set_rewatch_value, "likelihood of the user rewatching the series"]: RepeatValue,
+    [priority, set_priority, "priority the user has given the series"]: Priority,
+    [enable_discussion, set_enable_discussion, "discussion status of the series"]: bool,
+    [downloaded_episodes, set_downloaded_episodes, "number of downloaded episodes"]: u32,
 );
 
 impl_entryvalues!(AnimeValues,
-    watched_episodes(num): "episode" => num.to_string(),
-    status(status): "status" => (*status as i32).to_string(),
-    start_date(date): "date_start" => list::date_to_str(*date),
-    finish_date(date): "date_finish" => list::date_to_str(*date),
-    score(score): "score" => score.to_string(),
-    rewatching(v): "enable_rewatching" => (*v as u8).to_string(),
-    tags(t): "tags" => list::concat_by_delim(t, ','),
+    watched_episodes(num): "episode" => num.to_string();
+        "num_watched_episodes" => num.to_string(),
+    status(status): "status" => (*status as i32).to_string();
+        "status" => v2::status_to_str(*status, ListType::Anime),
+    start_date(date): "date_start" => list::date_to_str(*date);
+        "start_date" => v2::date_to_str(*date),
+    finish_date(date): "date_finish" => list::date_to_str(*date);
+        "finish_date" => v2::date_to_str(*date),
+    score(score): "score" => score.to_string();
+        "score" => score.to_string(),
+    rewatching(v): "enable_rewatching" => (*v as u8).to_string();
+        "is_rewatching" => v.to_string(),
+    tags(t): "tags" => list::concat_by_delim(t, ',');
+        "tags" => t.join(","),
+    storage_type(storage): "storage_type" => (*storage as i32).to_string(),
+    storage_value(value): "storage_value" => value.to_string(),
+    times_rewatched(num): "times_rewatched" => num.to_string();
+        "num_times_rewatched" => num.to_string(),
+    rewatch_value(value): "rewatch_value" => (*value as i32).to_string();
+        "rewatch_value" => (*value as i32).to_string(),
+    priority(priority): "priority" => (*priority as i32).to_string();
+        "priority" => (*priority as i32).to_string(),
+    enable_discussion(v): "enable_discussion" => (*v as u8).to_string(),
+    comments(c): "comments" => c.clone();
+        "comments" => c.clone(),
+    downloaded_episodes(num): "downloaded_episodes" => num.to_string(),
 );
 
 /// Contains list statistics and user information.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnimeUserInfo {
     /// The user's ID.
     pub user_id: u32,
@@ -373,4 +1032,43 @@ impl UserInfo for AnimeUserInfo {
 
         Ok(info)
     }
+
+    #[doc(hidden)]
+    fn from_export_xml(xml: &Element) -> Result<AnimeUserInfo, ListError> {
+        let info = AnimeUserInfo {
+            user_id: list::parse_xml_child(xml, "user_id")?,
+            watching: list::parse_xml_child(xml, "user_total_watching")?,
+            completed: list::parse_xml_child(xml, "user_total_completed")?,
+            on_hold: list::parse_xml_child(xml, "user_total_onhold")?,
+            dropped: list::parse_xml_child(xml, "user_total_dropped")?,
+            plan_to_watch: list::parse_xml_child(xml, "user_total_plantowatch")?,
+            days_spent_watching: 0.0,
+        };
+
+        Ok(info)
+    }
+
+    #[doc(hidden)]
+    fn to_export_xml(&self, username: &str) -> Element {
+        let counts = [
+            self.watching,
+            self.completed,
+            self.on_hold,
+            self.dropped,
+            self.plan_to_watch,
+        ];
+
+        export::info_elem(ListType::Anime, self.user_id, username, counts)
+    }
+
+    #[doc(hidden)]
+    fn status_counts(&self) -> StatusCounts {
+        StatusCounts {
+            watching_or_reading: self.watching,
+            completed: self.completed,
+            on_hold: self.on_hold,
+            dropped: self.dropped,
+            plan_to_watch_or_read: self.plan_to_watch,
+        }
+    }
 }
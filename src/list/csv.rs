@@ -0,0 +1,321 @@
+//! This module allows a user's list to be written to CSV files, and CSV files to be read back
+//! as updates to a user's list.
+//!
+//! Every file uses the same columns for both anime and manga lists:
+//!
+//! | Column | Contents |
+//! | ------ | -------- |
+//! | `id` | The ID of the series |
+//! | `title` | The title of the series |
+//! | `type` | The type of series, like `tv` or `manga` |
+//! | `status` | The watching / reading status, like `watching` or `plan_to_read` |
+//! | `progress` | The number of watched episodes or read chapters |
+//! | `score` | The user's score of the series |
+//! | `start_date` | The date the user started the series, formatted as `YYYY-MM-DD` |
+//! | `finish_date` | The date the user finished the series, formatted as `YYYY-MM-DD` |
+//! | `tags` | The user's tags for the series, separated by `;` |
+//! | `rewatching` | Whether or not the user is rewatching / rereading the series |
+//!
+//! When reading a file, only the `id` column is required, and the `title` and `type` columns
+//! are ignored. Every other column that has a value will be marked as changed on the resulting
+//! entry values, so blank cells will leave the value on the user's list untouched.
+//! To remove the start date, finish date, or tags of a series instead, set the cell to `-`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use mal::MAL;
+//! use mal::list::csv;
+//! use mal::list::anime::AnimeEntry;
+//! use std::fs::File;
+//!
+//! let mal = MAL::new("username", "password");
+//! let anime_list = mal.anime_list();
+//!
+//! // Write the user's list to a CSV file
+//! let list = anime_list.read().unwrap();
+//! list.write_csv(File::create("anime.csv").unwrap()).unwrap();
+//!
+//! // Send every row of an edited CSV file back to MyAnimeList as an update
+//! let mut csv = csv::read_updates::<AnimeEntry, _>(File::open("anime.csv").unwrap()).unwrap();
+//!
+//! for err in &csv.errors {
+//!     eprintln!("skipping line {}: {}", err.line, err.error);
+//! }
+//!
+//! for &mut (id, ref mut values) in &mut csv.updates {
+//!     anime_list.update_id(id, values).unwrap();
+//! }
+//! ```
+
+use chrono::NaiveDate;
+use csv::{ReaderBuilder, StringRecord, Writer};
+use error::{CsvRowError, ListError};
+use std::io::{Read, Write};
+use std::str::FromStr;
+use super::{ListEntries, ListEntry, Status};
+use v2;
+
+const COLUMNS: [&str; 10] = [
+    "id",
+    "title",
+    "type",
+    "status",
+    "progress",
+    "score",
+    "start_date",
+    "finish_date",
+    "tags",
+    "rewatching",
+];
+
+const TAG_DELIM: &str = ";";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// Written in a cell to remove its value, since a blank cell leaves the value untouched
+const CLEAR_VALUE: &str = "-";
+
+/// Contains the values of a row in a CSV file.
+///
+/// Values that are `None` were left blank in the file.
+/// Dates that are `Some(None)` and empty tags were cleared with a `-`.
+#[doc(hidden)]
+#[derive(Debug, Default, Clone)]
+pub struct CsvRow {
+    pub id: u32,
+    pub title: String,
+    pub series_type: String,
+    pub status: Option<Status>,
+    pub progress: Option<u32>,
+    pub score: Option<u8>,
+    pub start_date: Option<Option<NaiveDate>>,
+    pub finish_date: Option<Option<NaiveDate>>,
+    pub tags: Option<Vec<String>>,
+    pub rewatching: Option<bool>,
+}
+
+/// Contains the results of reading list updates from a CSV file.
+#[derive(Debug)]
+pub struct CsvUpdates<V> {
+    /// The ID of the series and the values to update it with for every valid row.
+    pub updates: Vec<(u32, V)>,
+    /// The errors of every row that couldn't be parsed.
+    pub errors: Vec<CsvRowError>,
+}
+
+impl<E: ListEntry> ListEntries<E> {
+    /// Writes every entry in the list to `writer` as CSV.
+    ///
+    /// See the [`csv`] module for the columns that are written.
+    ///
+    /// [`csv`]: ./csv/index.html
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<(), ListError> {
+        let mut writer = Writer::from_writer(writer);
+        writer.write_record(&COLUMNS).map_err(ListError::Csv)?;
+
+        for entry in &self.entries {
+            let row = entry.to_csv_row();
+
+            writer
+                .write_record(&[
+                    row.id.to_string(),
+                    row.title,
+                    row.series_type,
+                    opt_to_string(row.status.map(|s| v2::status_to_str(s, E::list_type()))),
+                    opt_to_string(row.progress),
+                    opt_to_string(row.score),
+                    date_to_string(row.start_date),
+                    date_to_string(row.finish_date),
+                    row.tags.map(|t| t.join(TAG_DELIM)).unwrap_or_default(),
+                    opt_to_string(row.rewatching),
+                ])
+                .map_err(ListError::Csv)?;
+        }
+
+        writer.flush().map_err(ListError::Io)
+    }
+}
+
+/// Reads updates for a user's list from CSV data.
+///
+/// Rows that can't be parsed are reported in the `errors` field of the returned [`CsvUpdates`]
+/// instead of stopping the whole file from being read. An error is only returned if the file
+/// itself can't be read, or if it doesn't have an `id` column.
+///
+/// See the [`csv`] module for the columns that are read.
+///
+/// [`CsvUpdates`]: ./struct.CsvUpdates.html
+/// [`csv`]: ./index.html
+///
+/// # Examples
+///
+/// ```
+/// use mal::list::{csv, Status};
+/// use mal::list::anime::AnimeEntry;
+///
+/// let data = "id,status,progress,score\n\
+///             4224,completed,25,9\n\
+///             1,watching,many,\n";
+///
+/// let csv = csv::read_updates::<AnimeEntry, _>(data.as_bytes()).unwrap();
+///
+/// let (id, ref values) = csv.updates[0];
+/// assert_eq!(id, 4224);
+/// assert_eq!(values.status(), Status::Completed);
+/// assert_eq!(values.watched_episodes(), 25);
+///
+/// // The second row has an invalid progress value
+/// assert_eq!(csv.errors.len(), 1);
+/// assert_eq!(csv.errors[0].line, 3);
+/// ```
+pub fn read_updates<E, R>(reader: R) -> Result<CsvUpdates<E::Values>, ListError>
+where
+    E: ListEntry,
+    R: Read,
+{
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = reader.headers().map_err(ListError::Csv)?.clone();
+
+    if column(&headers, "id").is_none() {
+        return Err(ListError::MissingCSVColumn("id".into()));
+    }
+
+    let mut updates = CsvUpdates {
+        updates: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    let mut record = StringRecord::new();
+
+    loop {
+        let line = reader.position().line();
+
+        match reader.read_record(&mut record) {
+            Ok(true) => (),
+            Ok(false) => break,
+            Err(err) => {
+                // The reader doesn't advance past I/O errors, so they would be returned forever
+                if err.is_io_error() {
+                    return Err(ListError::Csv(err));
+                }
+
+                updates.errors.push(CsvRowError {
+                    line,
+                    error: ListError::Csv(err),
+                });
+
+                continue;
+            }
+        }
+
+        let line = record.position().map(|pos| pos.line()).unwrap_or(line);
+
+        match parse_row(&headers, &record) {
+            Ok(row) => updates.updates.push((row.id, E::values_from_csv_row(&row))),
+            Err(error) => updates.errors.push(CsvRowError { line, error }),
+        }
+    }
+
+    Ok(updates)
+}
+
+fn parse_row(headers: &StringRecord, record: &StringRecord) -> Result<CsvRow, ListError> {
+    let row = CsvRow {
+        id: parse_field(headers, record, "id")?
+            .ok_or_else(|| ListError::CSVConversionFailed("id".into()))?,
+        title: String::new(),
+        series_type: String::new(),
+        status: match field(headers, record, "status") {
+            Some(status) => Some(
+                v2::status_from_str(&status.to_ascii_lowercase())
+                    .ok_or_else(|| ListError::UnknownStatus(status.into()))?,
+            ),
+            None => None,
+        },
+        progress: parse_field(headers, record, "progress")?,
+        score: parse_field(headers, record, "score")?,
+        start_date: parse_date(headers, record, "start_date")?,
+        finish_date: parse_date(headers, record, "finish_date")?,
+        tags: field(headers, record, "tags").map(|tags| {
+            if tags == CLEAR_VALUE {
+                return Vec::new();
+            }
+
+            tags.split(TAG_DELIM)
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect()
+        }),
+        rewatching: match field(headers, record, "rewatching") {
+            Some(value) => Some(parse_bool(value)
+                .ok_or_else(|| ListError::CSVConversionFailed("rewatching".into()))?),
+            None => None,
+        },
+    };
+
+    Ok(row)
+}
+
+fn column(headers: &StringRecord, name: &str) -> Option<usize> {
+    headers.iter().position(|header| header.trim() == name)
+}
+
+// Returns the trimmed value of a column, or None if the column doesn't exist or is blank
+fn field<'a>(headers: &StringRecord, record: &'a StringRecord, name: &str) -> Option<&'a str> {
+    column(headers, name)
+        .and_then(|index| record.get(index))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn parse_field<T>(
+    headers: &StringRecord,
+    record: &StringRecord,
+    name: &str,
+) -> Result<Option<T>, ListError>
+where
+    T: FromStr,
+{
+    match field(headers, record, name) {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| ListError::CSVConversionFailed(name.into())),
+        None => Ok(None),
+    }
+}
+
+fn parse_date(
+    headers: &StringRecord,
+    record: &StringRecord,
+    name: &str,
+) -> Result<Option<Option<NaiveDate>>, ListError> {
+    match field(headers, record, name) {
+        Some(CLEAR_VALUE) => Ok(Some(None)),
+        Some(value) => NaiveDate::parse_from_str(value, DATE_FORMAT)
+            .map(|date| Some(Some(date)))
+            .map_err(|_| ListError::CSVConversionFailed(name.into())),
+        None => Ok(None),
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn opt_to_string<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn date_to_string(date: Option<Option<NaiveDate>>) -> String {
+    match date {
+        Some(Some(date)) => date.format(DATE_FORMAT).to_string(),
+        Some(None) => CLEAR_VALUE.into(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,315 @@
+//! This module allows two snapshots of a user's list to be compared.
+//!
+//! The differences between two [`ListEntries`] are represented by a [`ListDiff`], which
+//! contains the entries that were added and removed, as well as the fields that changed on
+//! every entry present in both snapshots.
+//!
+//! [`ListEntries`]: ../struct.ListEntries.html
+//! [`ListDiff`]: ./struct.ListDiff.html
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use mal::MAL;
+//!
+//! let mal = MAL::new("username", "password");
+//! let anime_list = mal.anime_list();
+//!
+//! let before = anime_list.read().unwrap();
+//! // ...
+//! let after = anime_list.read().unwrap();
+//!
+//! let diff = before.diff(&after);
+//!
+//! for change in &diff.changed {
+//!     println!("{}", change);
+//! }
+//! ```
+
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use super::{ListEntries, ListEntry, Status};
+
+/// Represents a change to a single field of an entry.
+///
+/// The first value of every variant is the old value, and the second is the new value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldChange {
+    /// The watching / reading status changed.
+    Status(Status, Status),
+    /// The number of watched episodes or read chapters changed.
+    Progress(u32, u32),
+    /// The number of read volumes changed. This is only used for manga.
+    Volumes(u32, u32),
+    /// The user's score changed.
+    Score(u8, u8),
+    /// The date the user started the series changed.
+    StartDate(Option<NaiveDate>, Option<NaiveDate>),
+    /// The date the user finished the series changed.
+    FinishDate(Option<NaiveDate>, Option<NaiveDate>),
+    /// The user's tags changed.
+    Tags(Vec<String>, Vec<String>),
+}
+
+impl FieldChange {
+    /// Creates a `FieldChange` from two values if they are different.
+    #[doc(hidden)]
+    pub fn between<T, F>(old: T, new: T, change: F) -> Option<FieldChange>
+    where
+        T: PartialEq,
+        F: FnOnce(T, T) -> FieldChange,
+    {
+        if old != new {
+            Some(change(old, new))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FieldChange::Status(old, new) => write!(f, "status: {} -> {}", old, new),
+            FieldChange::Progress(old, new) => write!(f, "progress: {} -> {}", old, new),
+            FieldChange::Volumes(old, new) => write!(f, "volumes: {} -> {}", old, new),
+            FieldChange::Score(old, new) => write!(f, "score: {} -> {}", old, new),
+            FieldChange::StartDate(old, new) => {
+                write!(f, "start date: {} -> {}", DisplayDate(old), DisplayDate(new))
+            }
+            FieldChange::FinishDate(old, new) => {
+                write!(f, "finish date: {} -> {}", DisplayDate(old), DisplayDate(new))
+            }
+            FieldChange::Tags(ref old, ref new) => {
+                let added = new.iter().filter(|tag| !old.contains(tag));
+                let removed = old.iter().filter(|tag| !new.contains(tag));
+
+                write!(f, "tags:")?;
+
+                for tag in added {
+                    write!(f, " +{}", tag)?;
+                }
+
+                for tag in removed {
+                    write!(f, " -{}", tag)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+struct DisplayDate(Option<NaiveDate>);
+
+impl fmt::Display for DisplayDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(date) => write!(f, "{}", date),
+            None => write!(f, "none"),
+        }
+    }
+}
+
+/// Contains the changes made to an entry that is present in both snapshots of a list.
+#[derive(Debug, Clone)]
+pub struct EntryChanges {
+    /// The ID of the series.
+    pub id: u32,
+    /// The title of the series.
+    pub title: String,
+    /// The last time the entry was updated in the old snapshot.
+    pub old_updated_time: DateTime<Utc>,
+    /// The last time the entry was updated in the new snapshot.
+    pub new_updated_time: DateTime<Utc>,
+    /// The fields that changed.
+    pub changes: Vec<FieldChange>,
+}
+
+impl fmt::Display for EntryChanges {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}):", self.title, self.id)?;
+
+        for change in &self.changes {
+            write!(f, "\n  {}", change)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Contains the differences between two snapshots of a user's list.
+///
+/// # Examples
+///
+/// ```
+/// use mal::list::{ListEntries, Status};
+/// use mal::list::anime::{AiringStatus, AnimeEntry, AnimeInfo, AnimeType, AnimeUserInfo};
+/// use mal::list::diff::FieldChange;
+///
+/// # fn info(id: u32) -> AnimeInfo {
+/// #     AnimeInfo {
+/// #         id,
+/// #         title: "Toradora!".into(),
+/// #         english_title: None,
+/// #         synonyms: Vec::new(),
+/// #         episodes: 25,
+/// #         series_type: AnimeType::TV,
+/// #         airing_status: AiringStatus::FinishedAiring,
+/// #         start_date: None,
+/// #         end_date: None,
+/// #         synopsis: None,
+/// #         image_url: String::new(),
+/// #     }
+/// # }
+/// # fn user_info() -> AnimeUserInfo {
+/// #     AnimeUserInfo {
+/// #         user_id: 1,
+/// #         watching: 0,
+/// #         completed: 0,
+/// #         on_hold: 0,
+/// #         dropped: 0,
+/// #         plan_to_watch: 0,
+/// #         days_spent_watching: 0.0,
+/// #     }
+/// # }
+/// let old = ListEntries {
+///     user_info: user_info(),
+///     entries: vec![AnimeEntry::new(info(1)), AnimeEntry::new(info(2))],
+/// };
+///
+/// let mut new = ListEntries {
+///     user_info: user_info(),
+///     entries: vec![AnimeEntry::new(info(2)), AnimeEntry::new(info(3))],
+/// };
+///
+/// new.entries[0].values.set_watched_episodes(5);
+///
+/// let diff = old.diff(&new);
+///
+/// assert_eq!(diff.added[0].series_info.id, 3);
+/// assert_eq!(diff.removed[0].series_info.id, 1);
+/// assert_eq!(diff.changed[0].id, 2);
+/// assert_eq!(diff.changed[0].changes, vec![FieldChange::Progress(0, 5)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ListDiff<E: ListEntry> {
+    /// Entries that are only present in the new snapshot.
+    pub added: Vec<E>,
+    /// Entries that are only present in the old snapshot.
+    pub removed: Vec<E>,
+    /// Entries present in both snapshots that have changed, ordered by the time
+    /// they were last updated in the new snapshot.
+    pub changed: Vec<EntryChanges>,
+}
+
+impl<E: ListEntry> ListDiff<E> {
+    /// Returns true if there are no differences between the two snapshots.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl<E> ListDiff<E>
+where
+    E: ListEntry,
+    E::Values: Default,
+{
+    /// Returns the entry values needed to update every changed entry from its old state to
+    /// its new state, along with the ID of the series.
+    ///
+    /// Only the fields that changed are marked as changed on the returned values, so they can
+    /// be sent directly with [`List::update_id`].
+    ///
+    /// [`List::update_id`]: ../struct.List.html#method.update_id
+    pub fn updates(&self) -> Vec<(u32, E::Values)> {
+        self.changed
+            .iter()
+            .map(|entry| {
+                let mut values = E::Values::default();
+
+                for change in &entry.changes {
+                    E::apply_change(&mut values, change);
+                }
+
+                (entry.id, values)
+            })
+            .collect()
+    }
+}
+
+impl<E: ListEntry> fmt::Display for ListDiff<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for entry in &self.added {
+            writeln!(f, "added: {} ({})", entry.title(), entry.id())?;
+        }
+
+        for entry in &self.removed {
+            writeln!(f, "removed: {} ({})", entry.title(), entry.id())?;
+        }
+
+        for entry in &self.changed {
+            writeln!(f, "{}", entry)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<E: ListEntry + Clone> ListEntries<E> {
+    /// Compares the entries of this list with a newer snapshot of the same list.
+    ///
+    /// Entries are matched by the ID of their series.
+    pub fn diff(&self, newer: &ListEntries<E>) -> ListDiff<E> {
+        let old_entries = self.entries
+            .iter()
+            .map(|entry| (entry.id(), entry))
+            .collect::<HashMap<_, _>>();
+
+        let new_ids = newer.entries
+            .iter()
+            .map(ListEntry::id)
+            .collect::<HashSet<_>>();
+
+        let mut diff = ListDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+
+        for entry in &newer.entries {
+            let old = match old_entries.get(&entry.id()) {
+                Some(old) => old,
+                None => {
+                    diff.added.push(entry.clone());
+                    continue;
+                }
+            };
+
+            let changes = E::field_changes(old, entry);
+
+            if changes.is_empty() {
+                continue;
+            }
+
+            diff.changed.push(EntryChanges {
+                id: entry.id(),
+                title: entry.title().into(),
+                old_updated_time: old.last_updated_time(),
+                new_updated_time: entry.last_updated_time(),
+                changes,
+            });
+        }
+
+        diff.removed = self.entries
+            .iter()
+            .filter(|entry| !new_ids.contains(&entry.id()))
+            .cloned()
+            .collect();
+
+        diff.changed.sort_by_key(|entry| entry.new_updated_time);
+        diff
+    }
+}
@@ -0,0 +1,386 @@
+//! This module allows a user's list to be read from and written to the format MyAnimeList
+//! uses when exporting a list from its website.
+//!
+//! Exported lists can be loaded with [`ListEntries::read_export`], and written with
+//! [`ListEntries::write_export`]. Both plain and gzipped files are supported, although
+//! gzipped files require the `gzip` feature, which is enabled by default.
+//! [`ListEntries::read_export_with_options`] also returns the options the export was written
+//! with, so it can be written back out without losing anything.
+//!
+//! The export format contains less series information than the API does, so series
+//! information that isn't present will be set to a default value when reading an export.
+//!
+//! [`ListEntries::read_export`]: ../struct.ListEntries.html#method.read_export
+//! [`ListEntries::write_export`]: ../struct.ListEntries.html#method.write_export
+//! [`ListEntries::read_export_with_options`]: ../struct.ListEntries.html#method.read_export_with_options
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use mal::list::ListEntries;
+//! use mal::list::anime::AnimeEntry;
+//! use mal::list::export::ExportOptions;
+//! use std::fs::File;
+//!
+//! // Load a list exported from MyAnimeList
+//! let file = File::open("animelist.xml.gz").unwrap();
+//! let list = ListEntries::<AnimeEntry>::read_export(file).unwrap();
+//!
+//! // And write it back out again
+//! let options = ExportOptions {
+//!     username: "username".into(),
+//!     ..ExportOptions::default()
+//! };
+//!
+//! let file = File::create("animelist_backup.xml.gz").unwrap();
+//! list.write_export(file, &options).unwrap();
+//! ```
+
+use chrono::NaiveDate;
+use error::ListError;
+#[cfg(feature = "gzip")]
+use flate2::Compression;
+#[cfg(feature = "gzip")]
+use flate2::read::GzDecoder;
+#[cfg(feature = "gzip")]
+use flate2::write::GzEncoder;
+use minidom::Element;
+use request::ListType;
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use super::{ListEntries, ListEntry, Status, UserInfo};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Options used when writing a list in the export format.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// The name of the user the list belongs to.
+    pub username: String,
+    /// Whether or not MyAnimeList should overwrite existing entries on the user's list
+    /// when the export is imported.
+    pub update_on_import: bool,
+    /// The IDs of the series MyAnimeList should overwrite when the export is imported,
+    /// even if `update_on_import` isn't set.
+    pub update_on_import_ids: HashSet<u32>,
+    /// Whether or not the export should be gzipped, like the files MyAnimeList produces.
+    /// This defaults to true when the `gzip` feature is enabled.
+    pub gzip: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            username: String::new(),
+            update_on_import: false,
+            update_on_import_ids: HashSet::new(),
+            gzip: cfg!(feature = "gzip"),
+        }
+    }
+}
+
+impl<E: ListEntry> ListEntries<E> {
+    /// Reads a list in the format MyAnimeList uses when exporting a list.
+    ///
+    /// The input is automatically decompressed if it is gzipped. Without the `gzip` feature,
+    /// gzipped input will return `ListError::GzipDisabled`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mal::list::{ListEntries, Status};
+    /// use mal::list::anime::AnimeEntry;
+    ///
+    /// let export = r#"
+    ///     <myanimelist>
+    ///         <myinfo>
+    ///             <user_id>1</user_id>
+    ///             <user_name>username</user_name>
+    ///             <user_export_type>1</user_export_type>
+    ///             <user_total_anime>1</user_total_anime>
+    ///             <user_total_watching>0</user_total_watching>
+    ///             <user_total_completed>1</user_total_completed>
+    ///             <user_total_onhold>0</user_total_onhold>
+    ///             <user_total_dropped>0</user_total_dropped>
+    ///             <user_total_plantowatch>0</user_total_plantowatch>
+    ///         </myinfo>
+    ///         <anime>
+    ///             <series_animedb_id>4224</series_animedb_id>
+    ///             <series_title><![CDATA[Toradora!]]></series_title>
+    ///             <series_type>TV</series_type>
+    ///             <series_episodes>25</series_episodes>
+    ///             <my_watched_episodes>25</my_watched_episodes>
+    ///             <my_start_date>0000-00-00</my_start_date>
+    ///             <my_finish_date>0000-00-00</my_finish_date>
+    ///             <my_score>9</my_score>
+    ///             <my_status>Completed</my_status>
+    ///             <my_times_watched>1</my_times_watched>
+    ///             <update_on_import>0</update_on_import>
+    ///         </anime>
+    ///     </myanimelist>"#;
+    ///
+    /// let list = ListEntries::<AnimeEntry>::read_export(export.as_bytes()).unwrap();
+    /// let entry = &list.entries[0];
+    ///
+    /// assert_eq!(entry.series_info.title, "Toradora!");
+    /// assert_eq!(entry.values.status(), Status::Completed);
+    /// assert_eq!(entry.values.times_rewatched(), 1);
+    /// ```
+    #[inline]
+    pub fn read_export<R: Read>(reader: R) -> Result<ListEntries<E>, ListError> {
+        ListEntries::read_export_with_options(reader).map(|(list, _)| list)
+    }
+
+    /// Reads a list in the format MyAnimeList uses when exporting a list, along with the
+    /// options that would write it back out the same way.
+    ///
+    /// The returned options contain the name of the user, whether or not the input was gzipped,
+    /// and the ID of every entry that MyAnimeList should overwrite when the export is imported.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use mal::list::ListEntries;
+    /// use mal::list::anime::AnimeEntry;
+    /// use std::fs::File;
+    ///
+    /// let file = File::open("animelist.xml.gz").unwrap();
+    /// let (mut list, options) = ListEntries::<AnimeEntry>::read_export_with_options(file).unwrap();
+    ///
+    /// list.entries.retain(|entry| entry.series_info.episodes > 12);
+    ///
+    /// let file = File::create("animelist_long.xml.gz").unwrap();
+    /// list.write_export(file, &options).unwrap();
+    /// ```
+    pub fn read_export_with_options<R: Read>(
+        mut reader: R,
+    ) -> Result<(ListEntries<E>, ExportOptions), ListError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(ListError::Io)?;
+
+        let gzip = data.starts_with(&GZIP_MAGIC);
+
+        if gzip {
+            data = decompress(&data)?;
+        }
+
+        let xml = String::from_utf8(data).map_err(ListError::Utf8)?;
+        let root: Element = xml.parse().map_err(ListError::Minidom)?;
+
+        let info = root.children()
+            .find(|c| c.name() == "myinfo")
+            .ok_or(ListError::NoUserInfoFound)?;
+
+        let export_type = super::parse_xml_child::<u32>(info, "user_export_type")?;
+
+        if export_type != export_type_id(E::list_type()) {
+            return Err(ListError::WrongExportType(export_type));
+        }
+
+        let user_info = E::UserInfo::from_export_xml(info)?;
+        let tag = list_tag(E::list_type());
+
+        let mut options = ExportOptions {
+            username: super::parse_xml_child_or_default(info, "user_name")?,
+            gzip,
+            ..ExportOptions::default()
+        };
+
+        let mut entries = Vec::new();
+
+        for child in root.children().filter(|c| c.name() == tag) {
+            let entry = E::from_export_xml(child)?;
+
+            if super::parse_xml_child_or_default::<u8>(child, "update_on_import")? != 0 {
+                options.update_on_import_ids.insert(entry.id());
+            }
+
+            entries.push(entry);
+        }
+
+        Ok((ListEntries { user_info, entries }, options))
+    }
+
+    /// Writes the list in the format MyAnimeList uses when exporting a list.
+    ///
+    /// The output can be imported on MyAnimeList's website, or read back with
+    /// [`read_export`].
+    ///
+    /// Setting `gzip` in the options without the `gzip` feature will return
+    /// `ListError::GzipDisabled`.
+    ///
+    /// [`read_export`]: #method.read_export
+    pub fn write_export<W: Write>(
+        &self,
+        writer: W,
+        options: &ExportOptions,
+    ) -> Result<(), ListError> {
+        let mut root = Element::bare("myanimelist");
+        root.append_child(self.user_info.to_export_xml(&options.username));
+
+        for entry in &self.entries {
+            let update = options.update_on_import
+                || options.update_on_import_ids.contains(&entry.id());
+
+            let mut elem = entry.to_export_xml();
+            elem.append_child(text_elem("update_on_import", update as u8));
+            root.append_child(elem);
+        }
+
+        if options.gzip {
+            write_compressed(&root, writer)
+        } else {
+            let mut writer = writer;
+            root.write_to(&mut writer).map_err(ListError::Minidom)
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn decompress(data: &[u8]) -> Result<Vec<u8>, ListError> {
+    let mut decompressed = Vec::new();
+
+    GzDecoder::new(data)
+        .read_to_end(&mut decompressed)
+        .map_err(ListError::Io)?;
+
+    Ok(decompressed)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn decompress(_: &[u8]) -> Result<Vec<u8>, ListError> {
+    Err(ListError::GzipDisabled)
+}
+
+#[cfg(feature = "gzip")]
+fn write_compressed<W: Write>(root: &Element, writer: W) -> Result<(), ListError> {
+    let mut encoder = GzEncoder::new(writer, Compression::default());
+    root.write_to(&mut encoder).map_err(ListError::Minidom)?;
+    encoder.finish().map_err(ListError::Io)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "gzip"))]
+fn write_compressed<W: Write>(_: &Element, _: W) -> Result<(), ListError> {
+    Err(ListError::GzipDisabled)
+}
+
+fn export_type_id(list_type: ListType) -> u32 {
+    match list_type {
+        ListType::Anime => 1,
+        ListType::Manga => 2,
+    }
+}
+
+fn list_tag(list_type: ListType) -> &'static str {
+    match list_type {
+        ListType::Anime => "anime",
+        ListType::Manga => "manga",
+    }
+}
+
+// Builds the user info element of an export from the user's status counts, which are ordered
+// in the same way as the Status enum
+pub(crate) fn info_elem(
+    list_type: ListType,
+    user_id: u32,
+    username: &str,
+    counts: [u32; 5],
+) -> Element {
+    let (total, status_tags) = match list_type {
+        ListType::Anime => (
+            "user_total_anime",
+            ["user_total_watching", "user_total_plantowatch"],
+        ),
+        ListType::Manga => (
+            "user_total_manga",
+            ["user_total_reading", "user_total_plantoread"],
+        ),
+    };
+
+    let mut info = Element::bare("myinfo");
+    info.append_child(text_elem("user_id", user_id));
+    info.append_child(text_elem("user_name", username));
+    info.append_child(text_elem("user_export_type", export_type_id(list_type)));
+    info.append_child(text_elem(total, counts.iter().sum::<u32>()));
+    info.append_child(text_elem(status_tags[0], counts[0]));
+    info.append_child(text_elem("user_total_completed", counts[1]));
+    info.append_child(text_elem("user_total_onhold", counts[2]));
+    info.append_child(text_elem("user_total_dropped", counts[3]));
+    info.append_child(text_elem(status_tags[1], counts[4]));
+    info
+}
+
+pub(crate) fn text_elem<T: ToString>(name: &str, value: T) -> Element {
+    let mut elem = Element::bare(name);
+    let value = value.to_string();
+
+    if !value.is_empty() {
+        elem.append_text_node(value);
+    }
+
+    elem
+}
+
+pub(crate) fn parse_status(xml: &Element, name: &str) -> Result<Status, ListError> {
+    let status = super::parse_xml_child::<String>(xml, name)?;
+
+    let parsed = match status.to_ascii_lowercase().as_str() {
+        "watching" | "reading" => Status::WatchingOrReading,
+        "completed" => Status::Completed,
+        "on-hold" => Status::OnHold,
+        "dropped" => Status::Dropped,
+        "plan to watch" | "plan to read" => Status::PlanToWatchOrRead,
+        _ => return Err(ListError::UnknownStatus(status)),
+    };
+
+    Ok(parsed)
+}
+
+// Tags in an export are separated by commas, and usually a space as well
+pub(crate) fn parse_tags(xml: &Element, name: &str) -> Result<Vec<String>, ListError> {
+    let tags = super::parse_xml_child_or_default::<String>(xml, name)?;
+
+    let tags = tags.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect();
+
+    Ok(tags)
+}
+
+pub(crate) fn status_to_str(status: Status, list_type: ListType) -> &'static str {
+    match (status, list_type) {
+        (Status::WatchingOrReading, ListType::Anime) => "Watching",
+        (Status::WatchingOrReading, ListType::Manga) => "Reading",
+        (Status::Completed, _) => "Completed",
+        (Status::OnHold, _) => "On-Hold",
+        (Status::Dropped, _) => "Dropped",
+        (Status::PlanToWatchOrRead, ListType::Anime) => "Plan to Watch",
+        (Status::PlanToWatchOrRead, ListType::Manga) => "Plan to Read",
+    }
+}
+
+pub(crate) fn date_to_str(date: Option<NaiveDate>) -> String {
+    match date {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => "0000-00-00".into(),
+    }
+}
+
+// Capitalizes the first letter of every word, as the export format does for most values
+pub(crate) fn title_case(value: &str) -> String {
+    value
+        .split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
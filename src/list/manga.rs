@@ -1,22 +1,35 @@
 //! Contains the required data structures to search for manga on MyAnimeList and
 //! perform operations on a user's manga list.
 
-use chrono::{DateTime, NaiveDate, TimeZone, Utc};
-use error::ListError;
-use list::{self, Status};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use error::{ListError, Violation};
+use list::{self, EntrySummary, Priority, ReleaseStatus, RepeatValue, Status};
+#[cfg(feature = "csv")]
+use list::csv::CsvRow;
+#[cfg(feature = "cache")]
+use list::ListEntries;
+use list::diff::FieldChange;
+use list::export::{self, text_elem};
+use list::stats::StatusCounts;
 use minidom::Element;
 use request::ListType;
+#[cfg(feature = "cache")]
+use serde_json;
+use serde_json::Value;
 use SeriesInfo;
+use v2;
 use super::{ChangeTracker, EntryValues, ListEntry, UserInfo};
 
 /// Represents basic information of a manga series on MyAnimeList.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MangaInfo {
     /// The ID of the series.
     pub id: u32,
     /// The title of the series.
     pub title: String,
-    /// The English title of the series. It is not available on list entries.
+    /// The English title of the series.
+    /// It is not available on list entries when using the legacy API.
     pub english_title: Option<String>,
     /// The alternative titles for the series.
     pub synonyms: Vec<String>,
@@ -29,8 +42,10 @@ pub struct MangaInfo {
     /// The current publishing status of the series.
     pub publishing_status: PublishingStatus,
     /// The date the series started airing.
+    #[cfg_attr(feature = "serde", serde(with = "::list::serialize::date"))]
     pub start_date: Option<NaiveDate>,
     /// The date the series finished airing.
+    #[cfg_attr(feature = "serde", serde(with = "::list::serialize::date"))]
     pub end_date: Option<NaiveDate>,
     /// The description of the series.
     /// It is not available on list entries, and it is formatted as HTML.
@@ -71,6 +86,53 @@ impl SeriesInfo for MangaInfo {
 
         Ok(entry)
     }
+
+    #[doc(hidden)]
+    fn from_json(json: &Value) -> Result<MangaInfo, ListError> {
+        let entry = MangaInfo {
+            id: v2::parse_u32(json, "id")?,
+            title: v2::parse_string(json, "title")?,
+            english_title: v2::english_title(json),
+            synonyms: v2::synonyms(json),
+            chapters: v2::parse_u32(json, "num_chapters").unwrap_or(0),
+            volumes: v2::parse_u32(json, "num_volumes").unwrap_or(0),
+            series_type: {
+                let s_type = v2::parse_string(json, "media_type")?;
+
+                match s_type.as_str() {
+                    "light_novel" => MangaType::Novel,
+                    "one_shot" => MangaType::OneShot,
+                    "doujinshi" | "oel" => MangaType::Manga,
+                    _ => MangaType::from_str(&s_type)
+                        .ok_or_else(|| ListError::UnknownSeriesType(s_type))?,
+                }
+            },
+            publishing_status: {
+                let status = v2::parse_string(json, "status")?;
+
+                match status.as_str() {
+                    "currently_publishing" | "on_hiatus" => PublishingStatus::Publishing,
+                    "finished" | "discontinued" => PublishingStatus::Finished,
+                    "not_yet_published" => PublishingStatus::NotYetPublished,
+                    _ => return Err(ListError::UnknownStatus(status)),
+                }
+            },
+            start_date: v2::parse_date(json, "start_date"),
+            end_date: v2::parse_date(json, "end_date"),
+            synopsis: v2::parse_string(json, "synopsis").ok(),
+            image_url: v2::image_url(json),
+        };
+
+        Ok(entry)
+    }
+
+    #[doc(hidden)]
+    fn titles(&self) -> Vec<&str> {
+        let mut titles = vec![self.title.as_str()];
+        titles.extend(self.english_title.as_ref().map(String::as_str));
+        titles.extend(self.synonyms.iter().map(String::as_str));
+        titles
+    }
 }
 
 impl PartialEq for MangaInfo {
@@ -110,10 +172,12 @@ gen_list_field_enum!(PublishingStatus,
 
 /// Contains information about a manga series on a user's list.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MangaEntry {
     /// The general series information.
     pub series_info: MangaInfo,
     /// The last time the series was updated.
+    #[cfg_attr(feature = "serde", serde(with = "::list::serialize::datetime"))]
     pub last_updated_time: DateTime<Utc>,
     /// Contains values that can be set / updated on a user's list.
     pub values: MangaValues,
@@ -151,12 +215,102 @@ impl MangaEntry {
             values: MangaValues::new(),
         }
     }
+
+    /// Sets the number of read chapters and updates the entry's status and dates like
+    /// the MyAnimeList website does, using today's date.
+    ///
+    /// See [`set_progress_on`] for the rules that are applied.
+    ///
+    /// [`set_progress_on`]: #method.set_progress_on
+    #[inline]
+    pub fn set_progress(&mut self, chapters: u32) -> &mut MangaEntry {
+        self.set_progress_on(chapters, Local::today().naive_local())
+    }
+
+    /// Sets the number of read chapters and updates the entry's status and dates like
+    /// the MyAnimeList website does:
+    ///
+    /// * Once a chapter has been read, a series that was planned to be read is set to reading,
+    ///   and the start date is set to `date` if it hasn't been set yet.
+    /// * Once every chapter has been read, the series is set to completed, the number of read
+    ///   volumes is set to the series' number of volumes, and the finish date is set to `date`
+    ///   if it hasn't been set yet. If the series was being reread, the reread is finished and
+    ///   the number of times it was reread is incremented.
+    ///
+    /// The last chapter can only be detected when the series' number of chapters is known.
+    pub fn set_progress_on(&mut self, chapters: u32, date: NaiveDate) -> &mut MangaEntry {
+        {
+            let values = &mut self.values;
+            values.set_read_chapters(chapters);
+
+            if chapters > 0 {
+                if values.status() == Status::PlanToWatchOrRead {
+                    values.set_status(Status::WatchingOrReading);
+                }
+
+                if values.start_date().is_none() {
+                    values.set_start_date(Some(date));
+                }
+            }
+
+            let total = self.series_info.chapters;
+
+            if total > 0 && chapters >= total {
+                if values.rereading() {
+                    let times_reread = values.times_reread();
+
+                    values
+                        .set_rereading(false)
+                        .set_times_reread(times_reread + 1);
+                }
+
+                if values.status() != Status::Completed {
+                    values.set_status(Status::Completed);
+                }
+
+                let volumes = self.series_info.volumes;
+
+                if volumes > 0 && values.volume() != volumes {
+                    values.set_read_volumes(volumes);
+                }
+
+                if values.finish_date().is_none() {
+                    values.set_finish_date(Some(date));
+                }
+            }
+        }
+
+        self
+    }
 }
 
 impl ListEntry for MangaEntry {
     type Info = MangaInfo;
     type Values = MangaValues;
     type UserInfo = MangaUserInfo;
+    type SeriesType = MangaType;
+
+    #[doc(hidden)]
+    fn violations(&self) -> Vec<Violation> {
+        let mut violations = self.values.violations();
+        let values = &self.values;
+
+        if list::exceeds_total(&values.chapter, self.series_info.chapters) {
+            violations.push(Violation::TooManyChapters {
+                read: values.chapter.value,
+                total: self.series_info.chapters,
+            });
+        }
+
+        if list::exceeds_total(&values.volume, self.series_info.volumes) {
+            violations.push(Violation::TooManyVolumes {
+                read: values.volume.value,
+                total: self.series_info.volumes,
+            });
+        }
+
+        violations
+    }
 
     #[doc(hidden)]
     fn from_xml(xml: &Element) -> Result<MangaEntry, ListError> {
@@ -201,12 +355,327 @@ impl ListEntry for MangaEntry {
         Ok(entry)
     }
 
+    #[doc(hidden)]
+    fn from_json(json: &Value) -> Result<MangaEntry, ListError> {
+        let list_status = v2::field(json, "list_status")?;
+
+        let entry = MangaEntry {
+            series_info: MangaInfo::from_json(v2::field(json, "node")?)?,
+            last_updated_time: v2::parse_time(list_status, "updated_at")?,
+            values: MangaValues::from_json(list_status)?,
+        };
+
+        Ok(entry)
+    }
+
+    #[doc(hidden)]
+    fn from_export_xml(xml: &Element) -> Result<MangaEntry, ListError> {
+        let info = MangaInfo {
+            id: list::parse_xml_child(xml, "manga_mangadb_id")?,
+            title: list::parse_xml_child(xml, "manga_title")?,
+            english_title: None,
+            synonyms: Vec::new(),
+            chapters: list::parse_xml_child_or_default(xml, "manga_chapters")?,
+            volumes: list::parse_xml_child_or_default(xml, "manga_volumes")?,
+            // The export format doesn't include the type or publishing status of a series
+            series_type: MangaType::Manga,
+            publishing_status: PublishingStatus::Finished,
+            start_date: None,
+            end_date: None,
+            synopsis: None,
+            image_url: String::new(),
+        };
+
+        let values = MangaValues {
+            chapter: list::parse_xml_child::<u32>(xml, "my_read_chapters")?.into(),
+            volume: list::parse_xml_child::<u32>(xml, "my_read_volumes")?.into(),
+            status: export::parse_status(xml, "my_status")?.into(),
+            score: list::parse_xml_child::<u8>(xml, "my_score")?.into(),
+            start_date: {
+                list::parse_str_date(&list::parse_xml_child::<String>(xml, "my_start_date")?).into()
+            },
+            finish_date: {
+                list::parse_str_date(&list::parse_xml_child::<String>(xml, "my_finish_date")?)
+                    .into()
+            },
+            rereading: {
+                list::parse_xml_child_or_default::<u8>(xml, "my_rereading")
+                    .map(|v| v == 1)?
+                    .into()
+            },
+            tags: export::parse_tags(xml, "my_tags")?.into(),
+            times_reread: list::parse_xml_child_or_default::<u32>(xml, "my_times_read")?.into(),
+            reread_value: {
+                let value = list::parse_xml_child_or_default::<String>(xml, "my_reread_value")?;
+                RepeatValue::from_str(&value).unwrap_or_default().into()
+            },
+            priority: {
+                let priority = list::parse_xml_child_or_default::<String>(xml, "my_priority")?;
+                Priority::from_str(&priority).unwrap_or_default().into()
+            },
+            enable_discussion: {
+                list::parse_xml_child_or_default::<u8>(xml, "my_discuss")
+                    .map(|v| v == 1)?
+                    .into()
+            },
+            comments: list::parse_xml_child_or_default::<String>(xml, "my_comments")?.into(),
+            scan_group: {
+                list::parse_xml_child_or_default::<String>(xml, "my_scanalation_group")?.into()
+            },
+            retail_volumes: {
+                list::parse_xml_child_or_default::<u32>(xml, "my_retail_volumes")?.into()
+            },
+        };
+
+        let entry = MangaEntry {
+            series_info: info,
+            last_updated_time: Utc::now(),
+            values,
+        };
+
+        Ok(entry)
+    }
+
+    #[doc(hidden)]
+    fn to_export_xml(&self) -> Element {
+        let info = &self.series_info;
+        let values = &self.values;
+
+        let mut elem = Element::bare("manga");
+        elem.append_child(text_elem("manga_mangadb_id", info.id));
+        elem.append_child(text_elem("manga_title", &info.title));
+        elem.append_child(text_elem("manga_volumes", info.volumes));
+        elem.append_child(text_elem("manga_chapters", info.chapters));
+        elem.append_child(text_elem("my_id", 0));
+        elem.append_child(text_elem("my_read_volumes", values.volume()));
+        elem.append_child(text_elem("my_read_chapters", values.chapter()));
+        elem.append_child(text_elem("my_start_date", export::date_to_str(values.start_date())));
+        elem.append_child(text_elem("my_finish_date", export::date_to_str(values.finish_date())));
+        elem.append_child(text_elem("my_scanalation_group", values.scan_group()));
+        elem.append_child(text_elem("my_score", values.score()));
+        elem.append_child(text_elem("my_storage", ""));
+        elem.append_child(text_elem("my_retail_volumes", values.retail_volumes()));
+        elem.append_child(text_elem(
+            "my_status",
+            export::status_to_str(values.status(), ListType::Manga),
+        ));
+        elem.append_child(text_elem("my_comments", values.comments()));
+        elem.append_child(text_elem("my_times_read", values.times_reread()));
+        elem.append_child(text_elem("my_tags", values.tags().join(", ")));
+        elem.append_child(text_elem("my_priority", values.priority().as_str().to_uppercase()));
+        elem.append_child(text_elem(
+            "my_reread_value",
+            export::title_case(values.reread_value().as_str()),
+        ));
+        elem.append_child(text_elem("my_rereading", values.rereading() as u8));
+        elem.append_child(text_elem("my_discuss", values.enable_discussion() as u8));
+        elem.append_child(text_elem("my_sns", "default"));
+        elem
+    }
+
+    #[cfg(feature = "csv")]
+    #[doc(hidden)]
+    fn to_csv_row(&self) -> CsvRow {
+        CsvRow {
+            id: self.series_info.id,
+            title: self.series_info.title.clone(),
+            series_type: self.series_info.series_type.as_str().into(),
+            status: Some(self.values.status()),
+            progress: Some(self.values.chapter()),
+            score: Some(self.values.score()),
+            start_date: self.values.start_date().map(Some),
+            finish_date: self.values.finish_date().map(Some),
+            tags: Some(self.values.tags().clone()),
+            rewatching: Some(self.values.rereading()),
+        }
+    }
+
+    #[cfg(feature = "csv")]
+    #[doc(hidden)]
+    fn values_from_csv_row(row: &CsvRow) -> MangaValues {
+        let mut values = MangaValues::new();
+
+        if let Some(status) = row.status {
+            values.set_status(status);
+        }
+
+        if let Some(chapters) = row.progress {
+            values.set_read_chapters(chapters);
+        }
+
+        if let Some(score) = row.score {
+            values.set_score(score);
+        }
+
+        if let Some(start_date) = row.start_date {
+            values.set_start_date(start_date);
+        }
+
+        if let Some(finish_date) = row.finish_date {
+            values.set_finish_date(finish_date);
+        }
+
+        if let Some(ref tags) = row.tags {
+            *values.tags_mut() = tags.clone();
+        }
+
+        if let Some(rereading) = row.rewatching {
+            values.set_rereading(rereading);
+        }
+
+        values
+    }
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    #[inline]
+    fn list_to_json(list: &ListEntries<MangaEntry>) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(list)
+    }
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    #[inline]
+    fn list_from_json(json: Value) -> Result<ListEntries<MangaEntry>, serde_json::Error> {
+        serde_json::from_value(json)
+    }
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    #[inline]
+    fn entry_to_json(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    #[inline]
+    fn search_results_to_json(results: &[MangaInfo]) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(results)
+    }
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    #[inline]
+    fn search_results_from_json(json: Value) -> Result<Vec<MangaInfo>, serde_json::Error> {
+        serde_json::from_value(json)
+    }
+
+    #[doc(hidden)]
+    fn user_info_from_entries(entries: &[MangaEntry]) -> MangaUserInfo {
+        let count = |status| entries.iter().filter(|e| e.values.status() == status).count() as u32;
+
+        MangaUserInfo {
+            user_id: 0,
+            reading: count(Status::WatchingOrReading),
+            completed: count(Status::Completed),
+            on_hold: count(Status::OnHold),
+            dropped: count(Status::Dropped),
+            plan_to_read: count(Status::PlanToWatchOrRead),
+            days_spent_watching: 0.0,
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn values(&self) -> &MangaValues {
+        &self.values
+    }
+
     #[doc(hidden)]
     #[inline]
     fn values_mut(&mut self) -> &mut MangaValues {
         &mut self.values
     }
 
+    #[doc(hidden)]
+    #[inline]
+    fn title(&self) -> &str {
+        &self.series_info.title
+    }
+
+    #[doc(hidden)]
+    fn summary(&self) -> EntrySummary {
+        let info = &self.series_info;
+
+        EntrySummary {
+            id: info.id,
+            title: &info.title,
+            english_title: info.english_title.as_ref().map(String::as_str),
+            synonyms: &info.synonyms,
+            series_type: info.series_type.as_str(),
+            release_status: match info.publishing_status {
+                PublishingStatus::Publishing => ReleaseStatus::Ongoing,
+                PublishingStatus::Finished => ReleaseStatus::Finished,
+                PublishingStatus::NotYetPublished => ReleaseStatus::Upcoming,
+            },
+            status: self.values.status(),
+            score: self.values.score(),
+            progress: self.values.chapter(),
+            total: info.chapters,
+            tags: self.values.tags(),
+            start_date: self.values.start_date(),
+            finish_date: self.values.finish_date(),
+            last_updated_time: self.last_updated_time,
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn series_type(&self) -> MangaType {
+        self.series_info.series_type
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn last_updated_time(&self) -> DateTime<Utc> {
+        self.last_updated_time
+    }
+
+    #[doc(hidden)]
+    fn field_changes(old: &MangaEntry, new: &MangaEntry) -> Vec<FieldChange> {
+        let (old, new) = (&old.values, &new.values);
+
+        let changes = vec![
+            FieldChange::between(old.status(), new.status(), FieldChange::Status),
+            FieldChange::between(old.chapter(), new.chapter(), FieldChange::Progress),
+            FieldChange::between(old.volume(), new.volume(), FieldChange::Volumes),
+            FieldChange::between(old.score(), new.score(), FieldChange::Score),
+            FieldChange::between(old.start_date(), new.start_date(), FieldChange::StartDate),
+            FieldChange::between(old.finish_date(), new.finish_date(), FieldChange::FinishDate),
+            FieldChange::between(old.tags().clone(), new.tags().clone(), FieldChange::Tags),
+        ];
+
+        changes.into_iter().flatten().collect()
+    }
+
+    #[doc(hidden)]
+    fn apply_change(values: &mut MangaValues, change: &FieldChange) {
+        match *change {
+            FieldChange::Status(_, status) => {
+                values.set_status(status);
+            }
+            FieldChange::Progress(_, progress) => {
+                values.set_read_chapters(progress);
+            }
+            FieldChange::Volumes(_, volumes) => {
+                values.set_read_volumes(volumes);
+            }
+            FieldChange::Score(_, score) => {
+                values.set_score(score);
+            }
+            FieldChange::StartDate(_, date) => {
+                values.set_start_date(date);
+            }
+            FieldChange::FinishDate(_, date) => {
+                values.set_finish_date(date);
+            }
+            FieldChange::Tags(_, ref tags) => {
+                *values.tags_mut() = tags.clone();
+            }
+        }
+    }
+
     #[doc(hidden)]
     #[inline]
     fn set_last_updated_time(&mut self) {
@@ -247,15 +716,25 @@ impl ListEntry for MangaEntry {
 /// assert_eq!(values.score(), 7);
 /// ```
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MangaValues {
     chapter: ChangeTracker<u32>,
     volume: ChangeTracker<u32>,
     status: ChangeTracker<Status>,
     score: ChangeTracker<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "::list::serialize::tracked_date"))]
     start_date: ChangeTracker<Option<NaiveDate>>,
+    #[cfg_attr(feature = "serde", serde(with = "::list::serialize::tracked_date"))]
     finish_date: ChangeTracker<Option<NaiveDate>>,
     rereading: ChangeTracker<bool>,
     tags: ChangeTracker<Vec<String>>,
+    times_reread: ChangeTracker<u32>,
+    reread_value: ChangeTracker<RepeatValue>,
+    priority: ChangeTracker<Priority>,
+    enable_discussion: ChangeTracker<bool>,
+    comments: ChangeTracker<String>,
+    scan_group: ChangeTracker<String>,
+    retail_volumes: ChangeTracker<u32>,
 }
 
 impl MangaValues {
@@ -294,6 +773,51 @@ impl MangaValues {
             tags: {
                 list::split_by_delim(&list::parse_xml_child::<String>(xml, "my_tags")?, ",").into()
             },
+            times_reread: list::parse_xml_child_or_default::<u32>(xml, "my_times_reread")?.into(),
+            reread_value: {
+                let value = list::parse_xml_child_or_default(xml, "my_reread_value")?;
+                RepeatValue::from_i32(value).unwrap_or_default().into()
+            },
+            priority: {
+                let priority = list::parse_xml_child_or_default(xml, "my_priority")?;
+                Priority::from_i32(priority).unwrap_or_default().into()
+            },
+            enable_discussion: {
+                list::parse_xml_child_or_default::<u8>(xml, "my_enable_discussion")
+                    .map(|v| v == 1)?
+                    .into()
+            },
+            comments: list::parse_xml_child_or_default::<String>(xml, "my_comments")?.into(),
+            scan_group: list::parse_xml_child_or_default::<String>(xml, "my_scan_group")?.into(),
+            retail_volumes: {
+                list::parse_xml_child_or_default::<u32>(xml, "my_retail_volumes")?.into()
+            },
+        };
+
+        Ok(values)
+    }
+
+    fn from_json(json: &Value) -> Result<MangaValues, ListError> {
+        let values = MangaValues {
+            chapter: v2::parse_u32(json, "num_chapters_read")?.into(),
+            volume: v2::parse_u32(json, "num_volumes_read")?.into(),
+            status: v2::parse_status(json, "status")?.into(),
+            score: (v2::parse_u32(json, "score")? as u8).into(),
+            start_date: v2::parse_date(json, "start_date").into(),
+            finish_date: v2::parse_date(json, "finish_date").into(),
+            rereading: v2::parse_bool(json, "is_rereading").unwrap_or(false).into(),
+            tags: v2::parse_strings(json, "tags").into(),
+            times_reread: v2::parse_u32(json, "num_times_reread").unwrap_or(0).into(),
+            reread_value: {
+                let value = v2::parse_u32(json, "reread_value").unwrap_or(0);
+                RepeatValue::from_i32(value as i32).unwrap_or_default().into()
+            },
+            priority: {
+                let priority = v2::parse_u32(json, "priority").unwrap_or(0);
+                Priority::from_i32(priority as i32).unwrap_or_default().into()
+            },
+            comments: v2::parse_string(json, "comments").unwrap_or_default().into(),
+            ..MangaValues::default()
         };
 
         Ok(values)
@@ -313,6 +837,32 @@ impl MangaValues {
         self.tags.changed = true;
         &mut self.tags.value
     }
+
+    /// Returns the comments the user has written about the manga.
+    #[inline]
+    pub fn comments(&self) -> &str {
+        &self.comments.value
+    }
+
+    /// Sets the comments the user has written about the manga.
+    #[inline]
+    pub fn set_comments<S: Into<String>>(&mut self, comments: S) -> &mut MangaValues {
+        self.comments.set(comments.into());
+        self
+    }
+
+    /// Returns the scanlation group the user is reading the manga from.
+    #[inline]
+    pub fn scan_group(&self) -> &str {
+        &self.scan_group.value
+    }
+
+    /// Sets the scanlation group the user is reading the manga from.
+    #[inline]
+    pub fn set_scan_group<S: Into<String>>(&mut self, group: S) -> &mut MangaValues {
+        self.scan_group.set(group.into());
+        self
+    }
 }
 
 impl_tracker_getset!(MangaValues,
@@ -323,21 +873,46 @@ impl_tracker_getset!(MangaValues,
     [start_date, set_start_date, "date the user started reading the series"]: Option<NaiveDate>,
     [finish_date, set_finish_date, "date the user finished reading the series"]: Option<NaiveDate>,
     [rereading, set_rereading, "current re-reading status of the series"]: bool,
+    [times_reread, set_times_reread, "number of times the user has reread the series"]: u32,
+    [reread_value, set_reread_value, "likelihood of the user rereading the series"]: RepeatValue,
+    [priority, set_priority, "priority the user has given the series"]: Priority,
+    [enable_discussion, set_enable_discussion, "discussion status of the series"]: bool,
+    [retail_volumes, set_retail_volumes, "number of retail volumes the user owns"]: u32,
 );
 
 impl_entryvalues!(MangaValues,
-    chapter(num): "chapter" => num.to_string(),
-    volume(vol): "volume" => vol.to_string(),
-    status(status): "status" => (*status as i32).to_string(),
-    score(score): "score" => score.to_string(),
-    start_date(date): "date_start" => list::date_to_str(*date),
-    finish_date(date): "date_finish" => list::date_to_str(*date),
-    rereading(v): "enable_rereading" => (*v as u8).to_string(),
-    tags(t): "tags" => list::concat_by_delim(t, ','),
+    chapter(num): "chapter" => num.to_string();
+        "num_chapters_read" => num.to_string(),
+    volume(vol): "volume" => vol.to_string();
+        "num_volumes_read" => vol.to_string(),
+    status(status): "status" => (*status as i32).to_string();
+        "status" => v2::status_to_str(*status, ListType::Manga),
+    score(score): "score" => score.to_string();
+        "score" => score.to_string(),
+    start_date(date): "date_start" => list::date_to_str(*date);
+        "start_date" => v2::date_to_str(*date),
+    finish_date(date): "date_finish" => list::date_to_str(*date);
+        "finish_date" => v2::date_to_str(*date),
+    rereading(v): "enable_rereading" => (*v as u8).to_string();
+        "is_rereading" => v.to_string(),
+    tags(t): "tags" => list::concat_by_delim(t, ',');
+        "tags" => t.join(","),
+    times_reread(num): "times_reread" => num.to_string();
+        "num_times_reread" => num.to_string(),
+    reread_value(value): "reread_value" => (*value as i32).to_string();
+        "reread_value" => (*value as i32).to_string(),
+    priority(priority): "priority" => (*priority as i32).to_string();
+        "priority" => (*priority as i32).to_string(),
+    enable_discussion(v): "enable_discussion" => (*v as u8).to_string(),
+    comments(c): "comments" => c.clone();
+        "comments" => c.clone(),
+    scan_group(group): "scan_group" => group.clone(),
+    retail_volumes(num): "retail_volumes" => num.to_string(),
 );
 
 /// Contains list statistics and user information.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MangaUserInfo {
     /// The user's ID.
     pub user_id: u32,
@@ -370,4 +945,43 @@ impl UserInfo for MangaUserInfo {
 
         Ok(info)
     }
+
+    #[doc(hidden)]
+    fn from_export_xml(xml: &Element) -> Result<MangaUserInfo, ListError> {
+        let info = MangaUserInfo {
+            user_id: list::parse_xml_child(xml, "user_id")?,
+            reading: list::parse_xml_child(xml, "user_total_reading")?,
+            completed: list::parse_xml_child(xml, "user_total_completed")?,
+            on_hold: list::parse_xml_child(xml, "user_total_onhold")?,
+            dropped: list::parse_xml_child(xml, "user_total_dropped")?,
+            plan_to_read: list::parse_xml_child(xml, "user_total_plantoread")?,
+            days_spent_watching: 0.0,
+        };
+
+        Ok(info)
+    }
+
+    #[doc(hidden)]
+    fn to_export_xml(&self, username: &str) -> Element {
+        let counts = [
+            self.reading,
+            self.completed,
+            self.on_hold,
+            self.dropped,
+            self.plan_to_read,
+        ];
+
+        export::info_elem(ListType::Manga, self.user_id, username, counts)
+    }
+
+    #[doc(hidden)]
+    fn status_counts(&self) -> StatusCounts {
+        StatusCounts {
+            watching_or_reading: self.reading,
+            completed: self.completed,
+            on_hold: self.on_hold,
+            dropped: self.dropped,
+            plan_to_watch_or_read: self.plan_to_read,
+        }
+    }
 }
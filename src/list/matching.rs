@@ -0,0 +1,369 @@
+//! This module provides fuzzy matching of series titles, so the right series can be picked out of
+//! search results or a user's list from a name that doesn't exactly match its title on MyAnimeList.
+//!
+//! Titles are normalized before they're compared: case and punctuation are ignored, full-width
+//! characters are treated as their ASCII equivalents, and season suffixes like "2nd Season",
+//! "Season 2" and "S2" are all treated the same. Every title, English title and synonym of a
+//! series is compared, and the best one is used for its confidence.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use mal::MAL;
+//! use mal::list::matching::TitleMatcher;
+//!
+//! let mal = MAL::new("username", "password");
+//! let matcher = TitleMatcher::new("Toradora");
+//!
+//! // Look through the user's list first, so no search request has to be sent
+//! let list = mal.anime_list().read().unwrap();
+//!
+//! let id = match matcher.rank_entries(&list).first() {
+//!     Some(found) => found.item.series_info.id,
+//!     None => {
+//!         let results = mal.anime_list().search_for("Toradora").unwrap();
+//!         let ranked = matcher.rank_series(&results);
+//!         ranked[0].item.id
+//!     }
+//! };
+//! ```
+
+use std::cmp::Ordering;
+use super::{ListEntries, ListEntry, SeriesInfo};
+
+// The confidence matches need to have by default to be returned.
+const DEFAULT_MIN_CONFIDENCE: f32 = 0.5;
+
+// How much a title's confidence is scaled by when its season doesn't match.
+const SEASON_MISMATCH_FACTOR: f32 = 0.8;
+
+/// Represents a series or entry that matched a title.
+#[derive(Debug, Clone)]
+pub struct TitleMatch<'a, T: 'a> {
+    /// The series or entry that matched.
+    pub item: &'a T,
+    /// The title, English title or synonym of the series that matched best.
+    pub matched_title: &'a str,
+    /// How closely the title matched, from 0.0 to 1.0.
+    /// A confidence of 1.0 means the titles are the same after normalization.
+    pub confidence: f32,
+}
+
+/// Ranks series by how closely their titles match a title.
+///
+/// # Examples
+///
+/// ```
+/// use mal::list::anime::{AiringStatus, AnimeInfo, AnimeType};
+/// use mal::list::matching::TitleMatcher;
+///
+/// # fn info(id: u32, title: &str, english_title: Option<&str>) -> AnimeInfo {
+/// #     AnimeInfo {
+/// #         id,
+/// #         title: title.into(),
+/// #         english_title: english_title.map(Into::into),
+/// #         synonyms: Vec::new(),
+/// #         episodes: 12,
+/// #         series_type: AnimeType::TV,
+/// #         airing_status: AiringStatus::FinishedAiring,
+/// #         start_date: None,
+/// #         end_date: None,
+/// #         synopsis: None,
+/// #         image_url: String::new(),
+/// #     }
+/// # }
+/// let results = vec![
+///     info(1, "Shingeki no Kyojin", Some("Attack on Titan")),
+///     info(2, "Shingeki no Kyojin Season 2", Some("Attack on Titan Season 2")),
+///     info(3, "Mahou Shoujo Madoka★Magica", None),
+/// ];
+///
+/// let matcher = TitleMatcher::new("ATTACK ON TITAN 2nd Season");
+/// let ranked = matcher.rank_series(&results);
+///
+/// assert_eq!(ranked[0].item.id, 2);
+/// assert_eq!(ranked[0].matched_title, "Attack on Titan Season 2");
+/// assert_eq!(ranked[0].confidence, 1.0);
+///
+/// // Full-width characters and punctuation are normalized away
+/// assert_eq!(TitleMatcher::new("ｍａｄｏｋａ　ｍａｇｉｃａ").confidence("Madoka★Magica"), 1.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TitleMatcher {
+    title: NormalizedTitle,
+    min_confidence: f32,
+}
+
+impl TitleMatcher {
+    /// Creates a new `TitleMatcher` for the specified title.
+    ///
+    /// Only matches with a confidence of at least 0.5 are returned by default.
+    #[inline]
+    pub fn new(title: &str) -> TitleMatcher {
+        TitleMatcher {
+            title: NormalizedTitle::new(title),
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+        }
+    }
+
+    /// Sets the confidence matches need to have to be returned.
+    #[inline]
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> TitleMatcher {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Returns how closely the specified title matches the matcher's title, from 0.0 to 1.0.
+    pub fn confidence(&self, title: &str) -> f32 {
+        self.title.similarity(&NormalizedTitle::new(title))
+    }
+
+    /// Returns the series whose titles match, ordered from the most to the least confident match.
+    ///
+    /// This is intended to be used with the results of [`List::search_for`].
+    ///
+    /// [`List::search_for`]: ../struct.List.html#method.search_for
+    pub fn rank_series<'a, I: SeriesInfo>(&self, series: &'a [I]) -> Vec<TitleMatch<'a, I>> {
+        let matches = series
+            .iter()
+            .filter_map(|info| self.best_match(info, info.titles()))
+            .collect();
+
+        self.ranked(matches)
+    }
+
+    /// Returns the entries of a user's list whose titles match, ordered from the most to the
+    /// least confident match.
+    pub fn rank_entries<'a, E: ListEntry>(
+        &self,
+        list: &'a ListEntries<E>,
+    ) -> Vec<TitleMatch<'a, E>> {
+        let matches = list.entries
+            .iter()
+            .filter_map(|entry| {
+                let summary = entry.summary();
+
+                let mut titles = vec![summary.title];
+                titles.extend(summary.english_title);
+                titles.extend(summary.synonyms.iter().map(String::as_str));
+
+                self.best_match(entry, titles)
+            })
+            .collect();
+
+        self.ranked(matches)
+    }
+
+    fn best_match<'a, T>(&self, item: &'a T, titles: Vec<&'a str>) -> Option<TitleMatch<'a, T>> {
+        let mut best: Option<TitleMatch<T>> = None;
+
+        for title in titles {
+            let confidence = self.confidence(title);
+
+            // Earlier titles win ties, so the main title is preferred over synonyms
+            if best.as_ref().map(|b| confidence > b.confidence).unwrap_or(true) {
+                best = Some(TitleMatch {
+                    item,
+                    matched_title: title,
+                    confidence,
+                });
+            }
+        }
+
+        best
+    }
+
+    fn ranked<'a, T>(&self, mut matches: Vec<TitleMatch<'a, T>>) -> Vec<TitleMatch<'a, T>> {
+        matches.retain(|m| m.confidence >= self.min_confidence);
+
+        matches.sort_by(|a, b| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        matches
+    }
+}
+
+/// Normalizes a title the same way a [`TitleMatcher`] does before comparing titles.
+///
+/// [`TitleMatcher`]: ./struct.TitleMatcher.html
+///
+/// # Examples
+///
+/// ```
+/// use mal::list::matching::normalize_title;
+///
+/// assert_eq!(normalize_title("Toradora!"), "toradora");
+/// assert_eq!(normalize_title("K-ON!! 2nd Season"), "k on s2");
+/// assert_eq!(normalize_title("Ｋ－ＯＮ！！ Season 2"), "k on s2");
+/// assert_eq!(normalize_title("Haikyuu!! Season 1"), "haikyuu");
+/// ```
+pub fn normalize_title(title: &str) -> String {
+    let title = NormalizedTitle::new(title);
+
+    match title.season {
+        Some(season) => format!("{} s{}", title.words.join(" "), season),
+        None => title.words.join(" "),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NormalizedTitle {
+    words: Vec<String>,
+    // The first season is treated the same as not having a season
+    season: Option<u32>,
+}
+
+impl NormalizedTitle {
+    fn new(title: &str) -> NormalizedTitle {
+        let cleaned = title
+            .chars()
+            .map(to_half_width)
+            .filter(|&c| c != '\'' && c != '’')
+            .flat_map(char::to_lowercase)
+            .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+            .collect::<String>();
+
+        let mut words: Vec<String> = Vec::new();
+        let mut season = None;
+        let mut iter = cleaned.split_whitespace().peekable();
+
+        while let Some(word) = iter.next() {
+            if word == "season" {
+                // "Season 2"
+                if let Some(num) = iter.peek().and_then(|next| next.parse().ok()) {
+                    iter.next();
+                    season = Some(num);
+                    continue;
+                }
+
+                // "2nd Season" or "Second Season"
+                if let Some(num) = words.last().and_then(|prev| parse_ordinal(prev)) {
+                    words.pop();
+                    season = Some(num);
+                    continue;
+                }
+            }
+
+            // "S2"
+            if word.len() > 1 && word.starts_with('s') {
+                if let Ok(num) = word[1..].parse() {
+                    season = Some(num);
+                    continue;
+                }
+            }
+
+            words.push(word.into());
+        }
+
+        NormalizedTitle {
+            words,
+            season: season.filter(|&season| season > 1),
+        }
+    }
+
+    fn similarity(&self, other: &NormalizedTitle) -> f32 {
+        let confidence = if self.words == other.words {
+            1.0
+        } else {
+            let bigrams = dice(&bigrams(&self.words), &bigrams(&other.words));
+            let words = dice(&sorted(&self.words), &sorted(&other.words));
+
+            bigrams.max(words)
+        };
+
+        if self.season == other.season {
+            confidence
+        } else {
+            confidence * SEASON_MISMATCH_FACTOR
+        }
+    }
+}
+
+// Converts full-width ASCII variants and the ideographic space to their ASCII equivalents
+fn to_half_width(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '\u{FF01}'..='\u{FF5E}' => {
+            ::std::char::from_u32(c as u32 - 0xFEE0).unwrap_or(c)
+        }
+        _ => c,
+    }
+}
+
+fn parse_ordinal(word: &str) -> Option<u32> {
+    let num = match word {
+        "first" => 1,
+        "second" => 2,
+        "third" => 3,
+        "fourth" => 4,
+        "fifth" => 5,
+        "sixth" => 6,
+        "seventh" => 7,
+        "eighth" => 8,
+        "ninth" => 9,
+        "tenth" => 10,
+        _ => {
+            let is_ordinal = ["st", "nd", "rd", "th"]
+                .iter()
+                .any(|suffix| word.len() > suffix.len() && word.ends_with(suffix));
+
+            if !is_ordinal {
+                return None;
+            }
+
+            word[..word.len() - 2].parse().ok()?
+        }
+    };
+
+    Some(num)
+}
+
+// Returns the sorted character pairs of every word, with a leading space so words with a
+// single character still have one
+fn bigrams(words: &[String]) -> Vec<(char, char)> {
+    let mut pairs = Vec::new();
+
+    for word in words {
+        let mut prev = ' ';
+
+        for c in word.chars() {
+            pairs.push((prev, c));
+            prev = c;
+        }
+    }
+
+    pairs.sort();
+    pairs
+}
+
+fn sorted(words: &[String]) -> Vec<&str> {
+    let mut words = words.iter().map(String::as_str).collect::<Vec<_>>();
+    words.sort();
+    words
+}
+
+// Computes the Sørensen–Dice coefficient of two sorted lists
+fn dice<T: Ord>(a: &[T], b: &[T]) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let (mut i, mut j, mut shared) = (0, 0, 0);
+
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    (2 * shared) as f32 / (a.len() + b.len()) as f32
+}
@@ -8,15 +8,23 @@
 //! [`anime`]: ./anime/index.html
 //! [`manga`]: ./manga/index.html
 
-use chrono::NaiveDate;
-use error::{MALError, ListError, RequestError};
-use MAL;
+use chrono::{DateTime, NaiveDate, Utc};
+use error::{MALError, ListError, RequestError, Violation};
+use {Api, MAL};
 use minidom::Element;
-use request::{ListType, Request};
+use queue;
+use request::Request;
+use self::diff::FieldChange;
+use self::stats::StatusCounts;
 use reqwest::StatusCode;
+#[cfg(feature = "cache")]
+use serde_json;
+use serde_json::Value;
 use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
 use std::marker::PhantomData;
 use std::str::FromStr;
+use v2;
 
 // Generates getter and setter methods for struct fields with a ChangeTracker type.
 macro_rules! impl_tracker_getset {
@@ -47,7 +55,8 @@ macro_rules! impl_tracker_getset {
 // Generates enums that can be parsed from search results and a user's list
 macro_rules! gen_list_field_enum {
     ($name:ident, $([$field_doc:expr] $field:ident = [$field_index:expr, $field_str:expr],)+) => {
-        #[derive(Debug, Copy, Clone, PartialEq)]
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
         pub enum $name {
             $(
             #[doc = $field_doc]
@@ -64,6 +73,7 @@ macro_rules! gen_list_field_enum {
                 }
             }
 
+            #[allow(dead_code)]
             fn from_str<S: AsRef<str>>(input: S) -> Option<$name> {
                 let lowered = input.as_ref().to_ascii_lowercase();
 
@@ -72,15 +82,31 @@ macro_rules! gen_list_field_enum {
                     _ => None,
                 }
             }
+
+            #[allow(dead_code)]
+            pub(crate) fn as_str(&self) -> &'static str {
+                match *self {
+                    $($name::$field => $field_str,)+
+                }
+            }
         }
     };
 }
 
 // Convenience macro to implement the EntryValues trait without having to specify
-// struct fields multiple times
+// struct fields multiple times.
+// The optional name and value after the semicolon is used for version 2 of the API
 macro_rules! impl_entryvalues {
-    ($struct:ident, $($field:ident($val_name:ident): $xml_name:expr => $xml_val:expr,)+) => {
+    ($struct:ident, $($field:ident($val_name:ident): $xml_name:expr => $xml_val:expr
+        $(; $json_name:expr => $json_val:expr)*,)+) => {
         impl EntryValues for $struct {
+            // Every type of entry values has a score, start date, and finish date
+            #[doc(hidden)]
+            #[inline]
+            fn violations(&self) -> Vec<Violation> {
+                ::list::common_violations(&self.score, &self.start_date, &self.finish_date)
+            }
+
             #[doc(hidden)]
             fn add_changed_values(&self, xml_elem: &mut Element) {
                 $(if self.$field.changed {
@@ -92,18 +118,74 @@ macro_rules! impl_entryvalues {
                 })+
             }
 
+            #[doc(hidden)]
+            #[allow(unused_variables)]
+            fn add_changed_fields(&self, fields: &mut Vec<(&'static str, String)>) {
+                $(if self.$field.changed {
+                    let $val_name = &self.$field.value;
+                    $(fields.push(($json_name, $json_val));)*
+                })+
+            }
+
             #[doc(hidden)]
             fn reset_changed_fields(&mut self) {
                 $(self.$field.changed = false;)+
             }
+
+            #[doc(hidden)]
+            fn changed_field_names(&self) -> Vec<&'static str> {
+                let mut names = Vec::new();
+                $(if self.$field.changed {
+                    names.push(stringify!($field));
+                })+
+                names
+            }
+
+            #[doc(hidden)]
+            fn copy_changed_to(&self, other: &mut Self) {
+                $(if self.$field.changed {
+                    other.$field.value = self.$field.value.clone();
+                })+
+            }
+
+            #[doc(hidden)]
+            fn retain_differences(&mut self, other: &Self) -> bool {
+                $(self.$field.changed &= self.$field.value != other.$field.value;)+
+                false $(|| self.$field.changed)+
+            }
+
+            #[doc(hidden)]
+            fn mark_field_changed(&mut self, name: &str) -> bool {
+                match name {
+                    $(stringify!($field) => self.$field.changed = true,)+
+                    _ => return false,
+                }
+
+                true
+            }
         }
     };
 }
 
+pub mod affinity;
 #[cfg(feature = "anime")]
 pub mod anime;
+#[cfg(feature = "csv")]
+pub mod csv;
+pub mod diff;
+pub mod export;
 #[cfg(feature = "manga")]
 pub mod manga;
+pub mod matching;
+pub mod query;
+pub mod stats;
+pub mod sync;
+#[cfg(feature = "serde")]
+mod serialize;
+
+pub use request::ListType;
+#[cfg(feature = "serde")]
+pub use self::serialize::Tracked;
 
 /// This struct allows you to add, update, delete, and read entries to / from a user's list,
 /// as well as search for an anime / manga series.
@@ -158,7 +240,11 @@ impl<'a, E: ListEntry> List<'a, E> {
     /// Searches MyAnimeList for the type of series defined by the [`List`] instance
     /// and returns all found results.
     ///
+    /// When the `cache` feature is enabled and the [`MAL`] instance has a cache, results for
+    /// the same search will be returned from it until they expire.
+    ///
     /// [`List`]: ./struct.List.html
+    /// [`MAL`]: ../struct.MAL.html
     ///
     /// # Examples
     ///
@@ -177,32 +263,44 @@ impl<'a, E: ListEntry> List<'a, E> {
     where
         S: AsRef<str>,
     {
-        let resp = {
-            let result = Request::Search(name.as_ref(), E::list_type()).send(self.mal);
+        let name = name.as_ref();
 
-            match result {
-                Ok(resp) => resp,
-                Err(RequestError::BadResponseCode(StatusCode::NoContent)) => {
-                    return Ok(Vec::new());
-                }
-                Err(err) => return Err(MALError::Request(err)),
+        #[cfg(feature = "cache")]
+        {
+            let cached = self.mal
+                .cache
+                .as_ref()
+                .and_then(|cache| cache.search_results::<E>(self.mal, name));
+
+            if let Some(results) = cached {
+                return Ok(results);
             }
-        };
+        }
 
-        let root: Element = resp.parse().map_err(MALError::Minidom)?;
+        let resp = Request::Search(name, E::list_type()).send(self.mal);
 
-        let mut entries = Vec::new();
+        let results = match self.mal.api {
+            Api::Legacy => parse_search_results::<E>(resp)?,
+            Api::V2 { .. } => v2::parse_search_results::<E>(resp)?,
+        };
 
-        for child in root.children() {
-            let entry = E::Info::parse_search_result(child).map_err(MALError::List)?;
-            entries.push(entry);
+        #[cfg(feature = "cache")]
+        {
+            if let Some(ref cache) = self.mal.cache {
+                cache.store_search_results::<E>(self.mal, name, &results);
+            }
         }
 
-        Ok(entries)
+        Ok(results)
     }
 
     /// Requests and parses all entries on a user's list.
     ///
+    /// When the `cache` feature is enabled and the [`MAL`] instance has a cache, the cached list
+    /// will be returned instead until it expires.
+    ///
+    /// [`MAL`]: ../struct.MAL.html
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -218,35 +316,47 @@ impl<'a, E: ListEntry> List<'a, E> {
     /// println!("{:?}", list.entries);
     /// ```
     pub fn read(&self) -> Result<ListEntries<E>, MALError> {
-        let resp = Request::List(&self.mal.username, E::list_type())
-            .send(self.mal)
-            .map_err(MALError::Request)?;
+        #[cfg(feature = "cache")]
+        {
+            if let Some(list) = self.mal.cache.as_ref().and_then(|c| c.list(self.mal)) {
+                return Ok(list);
+            }
+        }
 
-        let root: Element = resp.parse().map_err(MALError::Minidom)?;
-        let mut children = root.children();
+        self.request_list()
+    }
 
-        let user_info = {
-            let elem = children
-                .next()
-                .ok_or_else(|| MALError::List(ListError::NoUserInfoFound))?;
+    // Requests the user's list from MyAnimeList even if it's cached, and caches the new list
+    pub(crate) fn request_list(&self) -> Result<ListEntries<E>, MALError> {
+        let list = match self.mal.api {
+            Api::Legacy => {
+                let resp = Request::List(&self.mal.username, E::list_type())
+                    .send(self.mal)
+                    .map_err(MALError::Request)?;
 
-            UserInfo::from_xml(elem).map_err(MALError::List)?
+                parse_list(&resp)?
+            }
+            Api::V2 { .. } => v2::read_list(self.mal, &self.mal.username)?,
         };
 
-        let mut entries = Vec::new();
-
-        for child in children {
-            let entry = E::from_xml(child).map_err(MALError::List)?;
-            entries.push(entry);
+        #[cfg(feature = "cache")]
+        {
+            if let Some(ref cache) = self.mal.cache {
+                cache.store_list(self.mal, &list);
+            }
         }
 
-        Ok(ListEntries { user_info, entries })
+        Ok(list)
     }
 
     /// Adds an entry to a user's list.
     ///
     /// If the entry is already on a user's list, nothing will happen.
     ///
+    /// The entry is checked with [`ListEntry::validate`] before anything is sent.
+    ///
+    /// [`ListEntry::validate`]: ./trait.ListEntry.html#method.validate
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -276,8 +386,17 @@ impl<'a, E: ListEntry> List<'a, E> {
     /// ```
     #[inline]
     pub fn add(&self, entry: &mut E) -> Result<(), MALError> {
-        self.add_id(entry.id(), entry.values_mut())?;
+        entry.validate().map_err(MALError::List)?;
+        self.send_add(entry.id(), entry.values_mut())?;
         entry.set_last_updated_time();
+
+        #[cfg(feature = "cache")]
+        {
+            if let Some(ref cache) = self.mal.cache {
+                cache.insert_entry(self.mal, entry);
+            }
+        }
+
         Ok(())
     }
 
@@ -285,6 +404,10 @@ impl<'a, E: ListEntry> List<'a, E> {
     ///
     /// If the entry is already on a user's list, nothing will happen.
     ///
+    /// The values are checked with [`EntryValues::validate`] before anything is sent.
+    ///
+    /// [`EntryValues::validate`]: ./trait.EntryValues.html#method.validate
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -306,7 +429,22 @@ impl<'a, E: ListEntry> List<'a, E> {
     /// mal.anime_list().add_id(4224, &mut values).unwrap();
     /// ```
     pub fn add_id(&self, id: u32, values: &mut E::Values) -> Result<(), MALError> {
-        let body = values.generate_xml().map_err(MALError::List)?;
+        values.validate().map_err(MALError::List)?;
+        self.send_add(id, values)?;
+
+        // The series info of the new entry isn't known, so it can't be added to the cached list
+        #[cfg(feature = "cache")]
+        {
+            if let Some(ref cache) = self.mal.cache {
+                cache.invalidate_list::<E>(self.mal);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn send_add(&self, id: u32, values: &mut E::Values) -> Result<(), MALError> {
+        let body = values.generate_body(&self.mal.api).map_err(MALError::List)?;
 
         Request::Add(id, E::list_type(), &body)
             .send(self.mal)
@@ -320,6 +458,10 @@ impl<'a, E: ListEntry> List<'a, E> {
     ///
     /// If the entry is already on a user's list, nothing will happen.
     ///
+    /// The entry is checked with [`ListEntry::validate`] before anything is sent.
+    ///
+    /// [`ListEntry::validate`]: ./trait.ListEntry.html#method.validate
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -353,6 +495,7 @@ impl<'a, E: ListEntry> List<'a, E> {
     /// ```
     #[inline]
     pub fn update(&self, entry: &mut E) -> Result<(), MALError> {
+        entry.validate().map_err(MALError::List)?;
         self.update_id(entry.id(), entry.values_mut())?;
         entry.set_last_updated_time();
         Ok(())
@@ -362,6 +505,16 @@ impl<'a, E: ListEntry> List<'a, E> {
     ///
     /// If the entry is already on the user's list, nothing will happen.
     ///
+    /// The values are checked with [`EntryValues::validate`] before anything is sent.
+    ///
+    /// If the [`MAL`] instance has a write queue and the update can't be sent because
+    /// MyAnimeList is unreachable, the update is queued and `Ok` is returned.
+    /// See the [`queue`] module for more info.
+    ///
+    /// [`EntryValues::validate`]: ./trait.EntryValues.html#method.validate
+    /// [`MAL`]: ../struct.MAL.html
+    /// [`queue`]: ../queue/index.html
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -384,11 +537,30 @@ impl<'a, E: ListEntry> List<'a, E> {
     /// mal.anime_list().update_id(4224, &mut values).unwrap();
     /// ```
     pub fn update_id(&self, id: u32, values: &mut E::Values) -> Result<(), MALError> {
-        let body = values.generate_xml().map_err(MALError::List)?;
+        values.validate().map_err(MALError::List)?;
+        let body = values.generate_body(&self.mal.api).map_err(MALError::List)?;
 
-        Request::Update(id, E::list_type(), &body)
-            .send(self.mal)
-            .map_err(MALError::Request)?;
+        let resp = Request::Update(id, E::list_type(), &body).send(self.mal);
+
+        match (resp, self.mal.write_queue.as_ref()) {
+            (Ok(_), _) => (),
+            (Err(ref err), Some(write_queue)) if queue::is_undeliverable(err) => {
+                write_queue
+                    .push_body(E::list_type(), id, &self.mal.api, body)
+                    .map_err(MALError::Queue)?;
+
+                values.reset_changed_fields();
+                return Ok(());
+            }
+            (Err(err), _) => return Err(MALError::Request(err)),
+        }
+
+        #[cfg(feature = "cache")]
+        {
+            if let Some(ref cache) = self.mal.cache {
+                cache.update_entry::<E>(self.mal, id, values);
+            }
+        }
 
         values.reset_changed_fields();
         Ok(())
@@ -449,16 +621,71 @@ impl<'a, E: ListEntry> List<'a, E> {
     /// ```
     #[inline]
     pub fn delete_id(&self, id: u32) -> Result<(), MALError> {
-        Request::Delete(id, E::list_type())
-            .send(self.mal)
-            .map_err(MALError::Request)?;
+        match Request::Delete(id, E::list_type()).send(self.mal) {
+            // Version 2 of the API returns a 404 when the entry isn't on the user's list
+            Ok(_) | Err(RequestError::BadResponseCode(StatusCode::NotFound)) => (),
+            Err(err) => return Err(MALError::Request(err)),
+        }
+
+        #[cfg(feature = "cache")]
+        {
+            if let Some(ref cache) = self.mal.cache {
+                cache.remove_entry::<E>(self.mal, id);
+            }
+        }
 
         Ok(())
     }
 }
 
+pub(crate) fn parse_search_results<E: ListEntry>(
+    resp: Result<String, RequestError>,
+) -> Result<Vec<E::Info>, MALError> {
+    let resp = match resp {
+        Ok(resp) => resp,
+        Err(RequestError::BadResponseCode(StatusCode::NoContent)) => {
+            return Ok(Vec::new());
+        }
+        Err(err) => return Err(MALError::Request(err)),
+    };
+
+    let root: Element = resp.parse().map_err(MALError::Minidom)?;
+
+    let mut entries = Vec::new();
+
+    for child in root.children() {
+        let entry = E::Info::parse_search_result(child).map_err(MALError::List)?;
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+pub(crate) fn parse_list<E: ListEntry>(resp: &str) -> Result<ListEntries<E>, MALError> {
+    let root: Element = resp.parse().map_err(MALError::Minidom)?;
+    let mut children = root.children();
+
+    let user_info = {
+        let elem = children
+            .next()
+            .ok_or_else(|| MALError::List(ListError::NoUserInfoFound))?;
+
+        UserInfo::from_xml(elem).map_err(MALError::List)?
+    };
+
+    let mut entries = Vec::new();
+
+    for child in children {
+        let entry = E::from_xml(child).map_err(MALError::List)?;
+        entries.push(entry);
+    }
+
+    Ok(ListEntries { user_info, entries })
+}
+
 /// Represents the watching / reading status of a series.
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Status {
     WatchingOrReading = 1,
     Completed,
@@ -514,6 +741,63 @@ impl Display for Status {
     }
 }
 
+/// Represents whether a series has been released, regardless of whether it's an anime or manga.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ReleaseStatus {
+    /// The series is currently airing / publishing.
+    Ongoing,
+    /// The series has finished airing / publishing.
+    Finished,
+    /// The series hasn't started airing / publishing yet.
+    Upcoming,
+}
+
+gen_list_field_enum!(Priority,
+    ["The series is a low priority."]
+    Low = [0, "low"],
+
+    ["The series is a medium priority."]
+    Medium = [1, "medium"],
+
+    ["The series is a high priority."]
+    High = [2, "high"],
+);
+
+impl Default for Priority {
+    #[inline]
+    fn default() -> Self {
+        Priority::Low
+    }
+}
+
+gen_list_field_enum!(RepeatValue,
+    ["The user hasn't said how likely they are to rewatch / reread the series."]
+    NotSet = [0, ""],
+
+    ["The user is very unlikely to rewatch / reread the series."]
+    VeryLow = [1, "very low"],
+
+    ["The user is unlikely to rewatch / reread the series."]
+    Low = [2, "low"],
+
+    ["The user may rewatch / reread the series."]
+    Medium = [3, "medium"],
+
+    ["The user is likely to rewatch / reread the series."]
+    High = [4, "high"],
+
+    ["The user is very likely to rewatch / reread the series."]
+    VeryHigh = [5, "very high"],
+);
+
+impl Default for RepeatValue {
+    #[inline]
+    fn default() -> Self {
+        RepeatValue::NotSet
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 struct ChangeTracker<T: Debug + Default + Clone> {
     value: T,
@@ -552,6 +836,17 @@ fn parse_xml_child<T: FromStr>(elem: &Element, name: &str) -> Result<T, ListErro
         .map_err(|_| ListError::XMLConversionFailed(name.into()))
 }
 
+// Used for values that aren't always present in a user's list
+fn parse_xml_child_or_default<T>(elem: &Element, name: &str) -> Result<T, ListError>
+where
+    T: FromStr + Default,
+{
+    match elem.children().find(|c| c.name() == name) {
+        Some(child) if !child.text().is_empty() => parse_xml_child(elem, name),
+        _ => Ok(T::default()),
+    }
+}
+
 fn parse_str_date(date: &str) -> Option<NaiveDate> {
     if date != "0000-00-00" {
         NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
@@ -570,6 +865,44 @@ fn date_to_str(date: Option<NaiveDate>) -> String {
     }
 }
 
+// Returns the violations of the values that anime and manga entries have in common.
+// Only changed values are checked, since they're the only ones that are sent.
+fn common_violations(
+    score: &ChangeTracker<u8>,
+    start_date: &ChangeTracker<Option<NaiveDate>>,
+    finish_date: &ChangeTracker<Option<NaiveDate>>,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    if score.changed && score.value > 10 {
+        violations.push(Violation::ScoreOutOfRange(score.value));
+    }
+
+    if start_date.changed || finish_date.changed {
+        if let (Some(start), Some(finish)) = (start_date.value, finish_date.value) {
+            if finish < start {
+                violations.push(Violation::FinishBeforeStart { start, finish });
+            }
+        }
+    }
+
+    violations
+}
+
+// Returns true if the progress is higher than the total of the series.
+// A total of 0 means the series hasn't finished airing / publishing yet, so it can't be checked.
+fn exceeds_total(progress: &ChangeTracker<u32>, total: u32) -> bool {
+    progress.changed && total > 0 && progress.value > total
+}
+
+fn validation_result(violations: Vec<Violation>) -> Result<(), ListError> {
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ListError::Validation(violations))
+    }
+}
+
 fn split_by_delim(string: &str, delim: &str) -> Vec<String> {
     string
         .split(delim)
@@ -584,6 +917,7 @@ fn concat_by_delim(tags: &[String], delim: char) -> String {
 
 /// Contains the results from parsing a user's list.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ListEntries<E: ListEntry> {
     /// General list statistics and info about the user.
     pub user_info: E::UserInfo,
@@ -591,6 +925,26 @@ pub struct ListEntries<E: ListEntry> {
     pub entries: Vec<E>,
 }
 
+// The values every type of list entry has, used to work with entries without knowing their type
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct EntrySummary<'a> {
+    pub id: u32,
+    pub title: &'a str,
+    pub english_title: Option<&'a str>,
+    pub synonyms: &'a [String],
+    pub series_type: &'static str,
+    pub release_status: ReleaseStatus,
+    pub status: Status,
+    pub score: u8,
+    pub progress: u32,
+    pub total: u32,
+    pub tags: &'a [String],
+    pub start_date: Option<NaiveDate>,
+    pub finish_date: Option<NaiveDate>,
+    pub last_updated_time: DateTime<Utc>,
+}
+
 /// Represents an entry on a user's list.
 pub trait ListEntry
 where
@@ -599,13 +953,90 @@ where
     type Info: SeriesInfo;
     type Values: EntryValues;
     type UserInfo: UserInfo;
+    /// The type of series the list contains, such as TV shows or one-shots.
+    type SeriesType: Copy + Eq + Hash + Debug;
+
+    /// Checks that MyAnimeList would accept the changed values of the entry as-is,
+    /// including the progress against the totals of the series when they're known.
+    ///
+    /// All problems that are found are returned in a [`ListError::Validation`] error.
+    ///
+    /// [`ListError::Validation`]: ../error/enum.ListError.html#variant.Validation
+    #[inline]
+    fn validate(&self) -> Result<(), ListError> {
+        validation_result(self.violations())
+    }
+
+    #[doc(hidden)]
+    fn violations(&self) -> Vec<Violation>;
 
     #[doc(hidden)]
     fn from_xml(xml_elem: &Element) -> Result<Self, ListError>;
 
+    #[doc(hidden)]
+    fn from_json(json: &Value) -> Result<Self, ListError>;
+
+    #[doc(hidden)]
+    fn from_export_xml(xml_elem: &Element) -> Result<Self, ListError>;
+
+    #[doc(hidden)]
+    fn to_export_xml(&self) -> Element;
+
+    #[cfg(feature = "csv")]
+    #[doc(hidden)]
+    fn to_csv_row(&self) -> self::csv::CsvRow;
+
+    #[cfg(feature = "csv")]
+    #[doc(hidden)]
+    fn values_from_csv_row(row: &self::csv::CsvRow) -> Self::Values;
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    fn list_to_json(list: &ListEntries<Self>) -> Result<Value, serde_json::Error>;
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    fn list_from_json(json: Value) -> Result<ListEntries<Self>, serde_json::Error>;
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    fn entry_to_json(&self) -> Result<Value, serde_json::Error>;
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    fn search_results_to_json(results: &[Self::Info]) -> Result<Value, serde_json::Error>;
+
+    #[cfg(feature = "cache")]
+    #[doc(hidden)]
+    fn search_results_from_json(json: Value) -> Result<Vec<Self::Info>, serde_json::Error>;
+
+    #[doc(hidden)]
+    fn user_info_from_entries(entries: &[Self]) -> Self::UserInfo;
+
+    #[doc(hidden)]
+    fn values(&self) -> &Self::Values;
+
     #[doc(hidden)]
     fn values_mut(&mut self) -> &mut Self::Values;
 
+    #[doc(hidden)]
+    fn title(&self) -> &str;
+
+    #[doc(hidden)]
+    fn summary(&self) -> EntrySummary;
+
+    #[doc(hidden)]
+    fn series_type(&self) -> Self::SeriesType;
+
+    #[doc(hidden)]
+    fn last_updated_time(&self) -> DateTime<Utc>;
+
+    #[doc(hidden)]
+    fn field_changes(old: &Self, new: &Self) -> Vec<FieldChange>;
+
+    #[doc(hidden)]
+    fn apply_change(values: &mut Self::Values, change: &FieldChange);
+
     #[doc(hidden)]
     fn set_last_updated_time(&mut self);
 
@@ -623,10 +1054,50 @@ where
 {
     #[doc(hidden)]
     fn parse_search_result(xml_elem: &Element) -> Result<Self, ListError>;
+
+    #[doc(hidden)]
+    fn from_json(json: &Value) -> Result<Self, ListError>;
+
+    #[doc(hidden)]
+    fn titles(&self) -> Vec<&str>;
 }
 
 /// Represents values on a user's list that can be set.
 pub trait EntryValues {
+    /// Checks that MyAnimeList would accept the changed values as-is.
+    ///
+    /// All problems that are found are returned in a [`ListError::Validation`] error.
+    /// Since the values don't contain any series information, progress isn't checked.
+    /// To check it as well, use [`ListEntry::validate`].
+    ///
+    /// [`ListError::Validation`]: ../error/enum.ListError.html#variant.Validation
+    /// [`ListEntry::validate`]: ./trait.ListEntry.html#method.validate
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mal::error::{ListError, Violation};
+    /// use mal::list::EntryValues;
+    /// use mal::list::anime::AnimeValues;
+    ///
+    /// let mut values = AnimeValues::new();
+    /// values.set_score(42);
+    ///
+    /// match values.validate() {
+    ///     Err(ListError::Validation(violations)) => {
+    ///         assert_eq!(violations, vec![Violation::ScoreOutOfRange(42)]);
+    ///     }
+    ///     _ => panic!("expected a validation error"),
+    /// }
+    /// ```
+    #[inline]
+    fn validate(&self) -> Result<(), ListError> {
+        validation_result(self.violations())
+    }
+
+    #[doc(hidden)]
+    fn violations(&self) -> Vec<Violation>;
+
     #[doc(hidden)]
     fn generate_xml(&self) -> Result<String, ListError> {
         let mut entry = Element::bare("entry");
@@ -638,11 +1109,42 @@ pub trait EntryValues {
         String::from_utf8(buffer).map_err(ListError::Utf8)
     }
 
+    #[doc(hidden)]
+    fn generate_form(&self) -> String {
+        let mut fields = Vec::new();
+        self.add_changed_fields(&mut fields);
+
+        v2::encode_form(&fields)
+    }
+
+    #[doc(hidden)]
+    fn generate_body(&self, api: &Api) -> Result<String, ListError> {
+        match *api {
+            Api::Legacy => self.generate_xml(),
+            Api::V2 { .. } => Ok(self.generate_form()),
+        }
+    }
+
     #[doc(hidden)]
     fn add_changed_values(&self, xml_elem: &mut Element);
 
+    #[doc(hidden)]
+    fn add_changed_fields(&self, fields: &mut Vec<(&'static str, String)>);
+
     #[doc(hidden)]
     fn reset_changed_fields(&mut self);
+
+    #[doc(hidden)]
+    fn changed_field_names(&self) -> Vec<&'static str>;
+
+    #[doc(hidden)]
+    fn copy_changed_to(&self, other: &mut Self);
+
+    #[doc(hidden)]
+    fn retain_differences(&mut self, other: &Self) -> bool;
+
+    #[doc(hidden)]
+    fn mark_field_changed(&mut self, name: &str) -> bool;
 }
 
 /// Represents info about a user's list.
@@ -652,4 +1154,13 @@ where
 {
     #[doc(hidden)]
     fn from_xml(xml_elem: &Element) -> Result<Self, ListError>;
+
+    #[doc(hidden)]
+    fn from_export_xml(xml_elem: &Element) -> Result<Self, ListError>;
+
+    #[doc(hidden)]
+    fn to_export_xml(&self, username: &str) -> Element;
+
+    #[doc(hidden)]
+    fn status_counts(&self) -> StatusCounts;
 }
@@ -0,0 +1,547 @@
+//! This module provides a way to filter and sort the entries of a user's list.
+//!
+//! A [`Query`] is made up of [`Filter`]s that an entry has to match, and [`SortKey`]s that the
+//! matching entries are sorted by. Queries can be built in code, or parsed from a string.
+//! Both anime and manga lists can be queried with the same [`Query`].
+//!
+//! [`Query`]: ./struct.Query.html
+//! [`Filter`]: ./enum.Filter.html
+//! [`SortKey`]: ./enum.SortKey.html
+//!
+//! # Syntax
+//!
+//! A query string is a list of terms separated by whitespace, and entries have to match every
+//! term. A term is either a `field:value` pair, or text that has to appear in the title of
+//! the series. Values that contain whitespace can be wrapped in double quotes, and terms can be
+//! negated by starting them with a `-`. Multiple values can be separated by commas to match
+//! any of them, except for titles.
+//!
+//! | Field | Values | Example |
+//! | --- | --- | --- |
+//! | `status` | `watching`, `reading`, `completed`, `on_hold`, `dropped`, `plan_to_watch`, `plan_to_read` | `status:watching,on_hold` |
+//! | `type` | The series type, such as `tv`, `movie`, `manga`, or `one-shot` | `type:tv` |
+//! | `release` | `ongoing`, `finished`, `upcoming` | `release:ongoing` |
+//! | `score` | A score or range of scores | `score:8..` |
+//! | `tag` | A tag | `tag:simulcast` |
+//! | `started` | A date or year, or range of them | `started:2017..2018-06-30` |
+//! | `finished` | A date or year, or range of them | `finished:2018` |
+//! | `progress` | A fraction or percentage, or range of them | `progress:..50%` |
+//! | `title` | Text in the title, English title, or synonyms of the series | `title:"steins gate"` |
+//! | `sort` | `title`, `score`, `status`, `progress`, `started`, `finished`, `updated`, with a `-` in front to sort in descending order | `sort:-score,title` |
+//!
+//! Ranges are written as `min..max`, and either side can be left out to leave it open.
+//! Dates are written as `YYYY-MM-DD`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use mal::MAL;
+//! use mal::list::query::Query;
+//!
+//! let mal = MAL::new("username", "password");
+//! let list = mal.anime_list().read().unwrap();
+//!
+//! // Find airing shows that are being watched with a score of at least 8 and tagged simulcast,
+//! // with the highest scores first
+//! let query = "release:ongoing status:watching score:8.. tag:simulcast sort:-score"
+//!     .parse::<Query>()
+//!     .unwrap();
+//!
+//! for entry in list.query(&query) {
+//!     println!("{}: {}", entry.series_info.title, entry.values.score());
+//! }
+//! ```
+
+use chrono::NaiveDate;
+use error::QueryError;
+use std::cmp::Ordering;
+use std::f32;
+use std::mem;
+use std::str::FromStr;
+use super::{EntrySummary, ListEntries, ListEntry, ReleaseStatus, Status};
+use v2;
+
+/// Represents a condition that an entry has to meet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Matches entries with the specified status.
+    Status(Status),
+    /// Matches series of the specified type, such as "tv" or "manga".
+    /// Case and punctuation are ignored, so "one_shot" will match one-shots.
+    SeriesType(String),
+    /// Matches series with the specified release status.
+    Release(ReleaseStatus),
+    /// Matches entries with a score between `min` and `max`, inclusive.
+    /// Entries that haven't been scored have a score of 0.
+    Score { min: u8, max: u8 },
+    /// Matches entries that have the specified tag. Case is ignored.
+    Tag(String),
+    /// Matches entries with a start date between `from` and `to`, inclusive.
+    /// A bound of `None` leaves that side of the range open.
+    /// Entries without a start date never match.
+    StartDate {
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    },
+    /// Matches entries with a finish date between `from` and `to`, inclusive.
+    /// A bound of `None` leaves that side of the range open.
+    /// Entries without a finish date never match.
+    FinishDate {
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    },
+    /// Matches entries whose watched episodes / read chapters divided by the total of the series
+    /// is between `min` and `max`, inclusive.
+    /// Entries of series with an unknown total never match.
+    Progress { min: f32, max: f32 },
+    /// Matches series whose title, English title, or synonyms contain the specified text.
+    /// Case is ignored.
+    Title(String),
+    /// Matches entries that don't match the inner filter.
+    Not(Box<Filter>),
+    /// Matches entries that match any of the inner filters.
+    Any(Vec<Filter>),
+}
+
+impl Filter {
+    /// Returns true if the specified entry meets the filter's condition.
+    #[inline]
+    pub fn matches<E: ListEntry>(&self, entry: &E) -> bool {
+        self.matches_summary(&entry.summary())
+    }
+
+    fn matches_summary(&self, entry: &EntrySummary) -> bool {
+        match *self {
+            Filter::Status(status) => entry.status == status,
+            Filter::SeriesType(ref series_type) => {
+                normalize(entry.series_type) == normalize(series_type)
+            }
+            Filter::Release(status) => entry.release_status == status,
+            Filter::Score { min, max } => entry.score >= min && entry.score <= max,
+            Filter::Tag(ref tag) => {
+                let tag = tag.to_lowercase();
+                entry.tags.iter().any(|t| t.to_lowercase() == tag)
+            }
+            Filter::StartDate { from, to } => in_range(entry.start_date, from, to),
+            Filter::FinishDate { from, to } => in_range(entry.finish_date, from, to),
+            Filter::Progress { min, max } => match progress_ratio(entry) {
+                Some(ratio) => ratio >= min && ratio <= max,
+                None => false,
+            },
+            Filter::Title(ref text) => {
+                let text = text.to_lowercase();
+
+                entry.title.to_lowercase().contains(&text)
+                    || entry
+                        .english_title
+                        .map(|title| title.to_lowercase().contains(&text))
+                        .unwrap_or(false)
+                    || entry
+                        .synonyms
+                        .iter()
+                        .any(|synonym| synonym.to_lowercase().contains(&text))
+            }
+            Filter::Not(ref filter) => !filter.matches_summary(entry),
+            Filter::Any(ref filters) => filters.iter().any(|f| f.matches_summary(entry)),
+        }
+    }
+}
+
+/// Represents a value entries can be sorted by.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SortKey {
+    /// Sorts by the title of the series, ignoring case.
+    Title,
+    /// Sorts by the user's score.
+    Score,
+    /// Sorts by status, in the order MyAnimeList displays them.
+    Status,
+    /// Sorts by watched episodes / read chapters divided by the total of the series.
+    /// Series with an unknown total are sorted before all others.
+    Progress,
+    /// Sorts by start date. Entries without one are sorted before all others.
+    StartDate,
+    /// Sorts by finish date. Entries without one are sorted before all others.
+    FinishDate,
+    /// Sorts by the last time the entry was updated.
+    LastUpdated,
+}
+
+impl SortKey {
+    fn compare(&self, a: &EntrySummary, b: &EntrySummary) -> Ordering {
+        match *self {
+            SortKey::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            SortKey::Score => a.score.cmp(&b.score),
+            SortKey::Status => (a.status as i32).cmp(&(b.status as i32)),
+            SortKey::Progress => progress_ratio(a)
+                .partial_cmp(&progress_ratio(b))
+                .unwrap_or(Ordering::Equal),
+            SortKey::StartDate => a.start_date.cmp(&b.start_date),
+            SortKey::FinishDate => a.finish_date.cmp(&b.finish_date),
+            SortKey::LastUpdated => a.last_updated_time.cmp(&b.last_updated_time),
+        }
+    }
+}
+
+/// Represents the direction entries are sorted in.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SortOrder {
+    /// Sorts from the lowest value to the highest.
+    Ascending,
+    /// Sorts from the highest value to the lowest.
+    Descending,
+}
+
+/// A set of filters and sort keys that can be applied to the entries of a list.
+///
+/// # Examples
+///
+/// ```
+/// use mal::list::Status;
+/// use mal::list::query::{Filter, Query, SortKey, SortOrder};
+///
+/// let query = Query::new()
+///     .filter(Filter::Status(Status::WatchingOrReading))
+///     .filter(Filter::Score { min: 8, max: 10 })
+///     .sort_by(SortKey::Score, SortOrder::Descending);
+///
+/// // The same query, parsed from a string
+/// let parsed = "status:watching score:8..10 sort:-score".parse::<Query>().unwrap();
+///
+/// assert_eq!(query, parsed);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    /// The filters an entry has to match. Entries have to match every filter.
+    pub filters: Vec<Filter>,
+    /// The keys matching entries are sorted by, in order of priority.
+    pub sort: Vec<(SortKey, SortOrder)>,
+}
+
+impl Query {
+    /// Creates a new `Query` that matches every entry and doesn't sort them.
+    #[inline]
+    pub fn new() -> Query {
+        Query::default()
+    }
+
+    /// Adds a filter that entries have to match.
+    #[inline]
+    pub fn filter(mut self, filter: Filter) -> Query {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Adds a key to sort matching entries by.
+    /// Keys that were added earlier take priority over later ones.
+    #[inline]
+    pub fn sort_by(mut self, key: SortKey, order: SortOrder) -> Query {
+        self.sort.push((key, order));
+        self
+    }
+
+    /// Returns true if the specified entry matches every filter of the query.
+    #[inline]
+    pub fn matches<E: ListEntry>(&self, entry: &E) -> bool {
+        let summary = entry.summary();
+        self.filters.iter().all(|f| f.matches_summary(&summary))
+    }
+
+    /// Returns every entry that matches the query, in the order specified by its sort keys.
+    ///
+    /// Entries that compare equal keep the order they're in.
+    pub fn apply<'a, E: ListEntry>(&self, entries: &'a [E]) -> Vec<&'a E> {
+        let mut results = entries
+            .iter()
+            .map(|entry| (entry, entry.summary()))
+            .filter(|result| self.filters.iter().all(|f| f.matches_summary(&result.1)))
+            .collect::<Vec<_>>();
+
+        if !self.sort.is_empty() {
+            results.sort_by(|a, b| self.compare(&a.1, &b.1));
+        }
+
+        results.into_iter().map(|(entry, _)| entry).collect()
+    }
+
+    fn compare(&self, a: &EntrySummary, b: &EntrySummary) -> Ordering {
+        for &(key, order) in &self.sort {
+            let ordering = match order {
+                SortOrder::Ascending => key.compare(a, b),
+                SortOrder::Descending => key.compare(b, a),
+            };
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+
+        Ordering::Equal
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(query: &str) -> Result<Query, QueryError> {
+        let mut result = Query::new();
+
+        for term in parse_terms(query)? {
+            let key = match term.key {
+                Some(ref key) => key.as_str(),
+                None if term.value.is_empty() => continue,
+                None => {
+                    result.filters.push(term.negate(Filter::Title(term.value.clone())));
+                    continue;
+                }
+            };
+
+            if term.value.is_empty() {
+                return Err(QueryError::MissingValue(key.into()));
+            }
+
+            match key {
+                "sort" => for value in term.value.split(',').filter(|v| !v.is_empty()) {
+                    let (descending, value) = if value.starts_with('-') {
+                        (true, &value[1..])
+                    } else {
+                        (false, value)
+                    };
+
+                    let key = parse_sort_key(value)
+                        .ok_or_else(|| QueryError::InvalidValue("sort".into(), value.into()))?;
+
+                    let order = if descending != term.negated {
+                        SortOrder::Descending
+                    } else {
+                        SortOrder::Ascending
+                    };
+
+                    result.sort.push((key, order));
+                },
+                "title" => {
+                    let filter = Filter::Title(term.value.clone());
+                    result.filters.push(term.negate(filter));
+                }
+                _ => {
+                    let mut filters = term.value
+                        .split(',')
+                        .filter(|v| !v.is_empty())
+                        .map(|value| parse_filter(key, value))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    let filter = if filters.len() == 1 {
+                        filters.remove(0)
+                    } else {
+                        Filter::Any(filters)
+                    };
+
+                    result.filters.push(term.negate(filter));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl<E: ListEntry> ListEntries<E> {
+    /// Returns every entry that matches the specified [`Query`], in the order specified by
+    /// its sort keys.
+    ///
+    /// [`Query`]: ./query/struct.Query.html
+    #[inline]
+    pub fn query(&self, query: &Query) -> Vec<&E> {
+        query.apply(&self.entries)
+    }
+}
+
+// A single term of a query string
+struct Term {
+    negated: bool,
+    key: Option<String>,
+    value: String,
+}
+
+impl Term {
+    fn negate(&self, filter: Filter) -> Filter {
+        if self.negated {
+            Filter::Not(Box::new(filter))
+        } else {
+            filter
+        }
+    }
+}
+
+fn parse_terms(query: &str) -> Result<Vec<Term>, QueryError> {
+    let mut terms = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    loop {
+        while chars.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+            chars.next();
+        }
+
+        let negated = match chars.peek() {
+            Some(&'-') => {
+                chars.next();
+                true
+            }
+            Some(_) => false,
+            None => break,
+        };
+
+        let mut term = Term {
+            negated,
+            key: None,
+            value: String::new(),
+        };
+
+        let mut quoted = false;
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() && !quoted {
+                break;
+            }
+
+            chars.next();
+
+            match c {
+                '"' => quoted = !quoted,
+                ':' if !quoted && term.key.is_none() => {
+                    let key = mem::replace(&mut term.value, String::new());
+                    term.key = Some(key.to_lowercase());
+                }
+                _ => term.value.push(c),
+            }
+        }
+
+        if quoted {
+            return Err(QueryError::UnterminatedQuote);
+        }
+
+        terms.push(term);
+    }
+
+    Ok(terms)
+}
+
+fn parse_filter(key: &str, value: &str) -> Result<Filter, QueryError> {
+    let filter = match key {
+        "status" => {
+            let status = value.to_lowercase().replace(|c| c == '-' || c == ' ', "_");
+            v2::status_from_str(&status).map(Filter::Status)
+        }
+        "type" => Some(Filter::SeriesType(value.into())),
+        "release" => match normalize(value).as_str() {
+            "ongoing" | "airing" | "publishing" => Some(Filter::Release(ReleaseStatus::Ongoing)),
+            "finished" => Some(Filter::Release(ReleaseStatus::Finished)),
+            "upcoming" => Some(Filter::Release(ReleaseStatus::Upcoming)),
+            _ => None,
+        },
+        "score" => parse_range(value, |s| s.parse().ok()).map(|(min, max)| Filter::Score {
+            min: min.unwrap_or(0),
+            max: max.unwrap_or_else(u8::max_value),
+        }),
+        "tag" => Some(Filter::Tag(value.into())),
+        "started" => parse_range(value, parse_date).map(|(from, to)| Filter::StartDate {
+            from: from.map(|range| range.0),
+            to: to.map(|range| range.1),
+        }),
+        "finished" => parse_range(value, parse_date).map(|(from, to)| Filter::FinishDate {
+            from: from.map(|range| range.0),
+            to: to.map(|range| range.1),
+        }),
+        "progress" => parse_range(value, parse_ratio).map(|(min, max)| Filter::Progress {
+            min: min.unwrap_or(0.0),
+            max: max.unwrap_or(f32::MAX),
+        }),
+        _ => return Err(QueryError::UnknownField(key.into())),
+    };
+
+    filter.ok_or_else(|| QueryError::InvalidValue(key.into(), value.into()))
+}
+
+fn parse_sort_key(value: &str) -> Option<SortKey> {
+    match value.to_lowercase().as_str() {
+        "title" => Some(SortKey::Title),
+        "score" => Some(SortKey::Score),
+        "status" => Some(SortKey::Status),
+        "progress" => Some(SortKey::Progress),
+        "started" => Some(SortKey::StartDate),
+        "finished" => Some(SortKey::FinishDate),
+        "updated" => Some(SortKey::LastUpdated),
+        _ => None,
+    }
+}
+
+// Parses either a single value or a range in the form of "min..max", where both sides are optional
+fn parse_range<T, F>(value: &str, parse: F) -> Option<(Option<T>, Option<T>)>
+where
+    T: Copy,
+    F: Fn(&str) -> Option<T>,
+{
+    let bound = |s: &str| {
+        if s.is_empty() {
+            Some(None)
+        } else {
+            parse(s).map(Some)
+        }
+    };
+
+    match value.find("..") {
+        Some(i) => Some((bound(&value[..i])?, bound(&value[i + 2..])?)),
+        None => {
+            let value = parse(value)?;
+            Some((Some(value), Some(value)))
+        }
+    }
+}
+
+// Returns the first and last day the date covers, so a year can be used as a range
+fn parse_date(value: &str) -> Option<(NaiveDate, NaiveDate)> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some((date, date));
+    }
+
+    let year = value.parse().ok()?;
+    let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+    let last = NaiveDate::from_ymd_opt(year, 12, 31)?;
+
+    Some((first, last))
+}
+
+fn parse_ratio(value: &str) -> Option<f32> {
+    if value.ends_with('%') {
+        value[..value.len() - 1].parse::<f32>().ok().map(|p| p / 100.0)
+    } else {
+        value.parse().ok()
+    }
+}
+
+fn in_range(date: Option<NaiveDate>, from: Option<NaiveDate>, to: Option<NaiveDate>) -> bool {
+    match date {
+        Some(date) => {
+            from.map(|f| date >= f).unwrap_or(true) && to.map(|t| date <= t).unwrap_or(true)
+        }
+        None => false,
+    }
+}
+
+fn progress_ratio(entry: &EntrySummary) -> Option<f32> {
+    if entry.total > 0 {
+        Some(entry.progress as f32 / entry.total as f32)
+    } else {
+        None
+    }
+}
+
+// Lowercases the string and removes everything but letters and numbers
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
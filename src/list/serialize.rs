@@ -0,0 +1,189 @@
+//! Serde support for the types in the `list` module.
+//!
+//! Dates are handled here instead of through chrono's `serde` feature so that the `serde`
+//! feature of this crate doesn't need to enable features of other crates.
+
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use std::fmt::Debug;
+use super::{ChangeTracker, EntryValues};
+
+/// Wraps [`EntryValues`] so the fields that have been changed are kept when serializing and
+/// deserializing.
+///
+/// [`EntryValues`] serialize as plain values by default, so deserializing them will result in
+/// no fields being marked as changed. Wrapping them in `Tracked` serializes the names of the
+/// changed fields alongside the values, which allows a deserialized instance to still be
+/// sent as a partial update.
+///
+/// [`EntryValues`]: ./trait.EntryValues.html
+///
+/// # Examples
+///
+/// ```
+/// extern crate mal;
+/// extern crate serde_json;
+///
+/// use mal::list::{EntryValues, Status, Tracked};
+/// use mal::list::anime::AnimeValues;
+///
+/// # fn main() {
+/// let mut values = AnimeValues::new();
+/// values.set_watched_episodes(5).set_status(Status::Completed);
+///
+/// let json = serde_json::to_string(&Tracked(values)).unwrap();
+/// let Tracked(values) = serde_json::from_str::<Tracked<AnimeValues>>(&json).unwrap();
+///
+/// assert_eq!(values.watched_episodes(), 5);
+/// assert_eq!(values.changed_field_names(), vec!["watched_episodes", "status"]);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tracked<V: EntryValues>(pub V);
+
+#[derive(Serialize)]
+struct TrackedRef<'a, V: 'a> {
+    values: &'a V,
+    changed: Vec<&'static str>,
+}
+
+#[derive(Deserialize)]
+struct TrackedOwned<V> {
+    values: V,
+    changed: Vec<String>,
+}
+
+impl<V> Serialize for Tracked<V>
+where
+    V: EntryValues + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let tracked = TrackedRef {
+            values: &self.0,
+            changed: self.0.changed_field_names(),
+        };
+
+        tracked.serialize(serializer)
+    }
+}
+
+impl<'de, V> Deserialize<'de> for Tracked<V>
+where
+    V: EntryValues + DeserializeOwned,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tracked = TrackedOwned::<V>::deserialize(deserializer)?;
+        let mut values = tracked.values;
+
+        for name in &tracked.changed {
+            if !values.mark_field_changed(name) {
+                return Err(de::Error::custom(format!("unknown changed field: {}", name)));
+            }
+        }
+
+        Ok(Tracked(values))
+    }
+}
+
+impl<T> Serialize for ChangeTracker<T>
+where
+    T: Debug + Default + Clone + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for ChangeTracker<T>
+where
+    T: Debug + Default + Clone + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(ChangeTracker::new)
+    }
+}
+
+/// (De)serializes an `Option<NaiveDate>` as an optional `YYYY-MM-DD` string.
+pub mod date {
+    use chrono::NaiveDate;
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::ser::Serializer;
+
+    const FORMAT: &str = "%Y-%m-%d";
+
+    pub fn serialize<S>(date: &Option<NaiveDate>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *date {
+            Some(date) => serializer.serialize_some(&date.format(FORMAT).to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(date) => NaiveDate::parse_from_str(&date, FORMAT)
+                .map(Some)
+                .map_err(de::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
+/// (De)serializes a tracked `Option<NaiveDate>` in the same format as [`date`].
+///
+/// [`date`]: ./date/index.html
+pub mod tracked_date {
+    use chrono::NaiveDate;
+    use serde::de::Deserializer;
+    use serde::ser::Serializer;
+    use super::super::ChangeTracker;
+
+    pub fn serialize<S>(
+        date: &ChangeTracker<Option<NaiveDate>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::date::serialize(&date.value, serializer)
+    }
+
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<ChangeTracker<Option<NaiveDate>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::date::deserialize(deserializer).map(ChangeTracker::new)
+    }
+}
+
+/// (De)serializes a `DateTime<Utc>` as an RFC 3339 string.
+pub mod datetime {
+    use chrono::{DateTime, Utc};
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::ser::Serializer;
+
+    pub fn serialize<S>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&time.to_rfc3339())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let time = String::deserialize(deserializer)?;
+
+        DateTime::parse_from_rfc3339(&time)
+            .map(|time| time.with_timezone(&Utc))
+            .map_err(de::Error::custom)
+    }
+}
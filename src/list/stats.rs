@@ -0,0 +1,234 @@
+//! This module provides statistics that are computed from the entries of a user's list,
+//! rather than the few counters MyAnimeList reports with it.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use mal::MAL;
+//!
+//! let mal = MAL::new("username", "password");
+//! let list = mal.anime_list().read().unwrap();
+//! let stats = list.stats();
+//!
+//! if let Some(mean) = stats.mean_score {
+//!     println!("mean score: {:.2}", mean);
+//! }
+//!
+//! for (year, completed) in &stats.completed_per_year {
+//!     println!("{}: {} completed", year, completed);
+//! }
+//!
+//! // The list MyAnimeList sent might not match the counters it reported with it
+//! for mismatch in &stats.mismatches {
+//!     println!("{:?}: {} on the list, {} reported", mismatch.status, mismatch.local, mismatch.server);
+//! }
+//! ```
+
+use chrono::Datelike;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use super::{ListEntries, ListEntry, Status, UserInfo};
+
+const STATUSES: [Status; 5] = [
+    Status::WatchingOrReading,
+    Status::Completed,
+    Status::OnHold,
+    Status::Dropped,
+    Status::PlanToWatchOrRead,
+];
+
+/// Contains the number of entries with each status.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct StatusCounts {
+    /// The number of series being watched / read.
+    pub watching_or_reading: u32,
+    /// The number of series that have been completed.
+    pub completed: u32,
+    /// The number of series on hold.
+    pub on_hold: u32,
+    /// The number of series dropped.
+    pub dropped: u32,
+    /// The number of series that are planning to be watched / read.
+    pub plan_to_watch_or_read: u32,
+}
+
+impl StatusCounts {
+    /// Returns the number of entries with the specified status.
+    #[inline]
+    pub fn get(&self, status: Status) -> u32 {
+        match status {
+            Status::WatchingOrReading => self.watching_or_reading,
+            Status::Completed => self.completed,
+            Status::OnHold => self.on_hold,
+            Status::Dropped => self.dropped,
+            Status::PlanToWatchOrRead => self.plan_to_watch_or_read,
+        }
+    }
+
+    /// Returns the number of entries across every status.
+    #[inline]
+    pub fn total(&self) -> u32 {
+        STATUSES.iter().map(|&status| self.get(status)).sum()
+    }
+
+    fn increment(&mut self, status: Status) {
+        let count = match status {
+            Status::WatchingOrReading => &mut self.watching_or_reading,
+            Status::Completed => &mut self.completed,
+            Status::OnHold => &mut self.on_hold,
+            Status::Dropped => &mut self.dropped,
+            Status::PlanToWatchOrRead => &mut self.plan_to_watch_or_read,
+        };
+
+        *count += 1;
+    }
+}
+
+/// Represents a status whose number of entries on the list doesn't match the number
+/// MyAnimeList reported.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StatusMismatch {
+    /// The status with mismatched counts.
+    pub status: Status,
+    /// The number of entries with the status on the list.
+    pub local: u32,
+    /// The number of entries with the status MyAnimeList reported.
+    pub server: u32,
+}
+
+/// Contains statistics computed from the entries of a list.
+///
+/// The `T` type parameter is the type of series the list contains, such as [`AnimeType`] or
+/// [`MangaType`].
+///
+/// [`AnimeType`]: ../anime/enum.AnimeType.html
+/// [`MangaType`]: ../manga/enum.MangaType.html
+#[derive(Debug, Clone)]
+pub struct ListStats<T: Eq + Hash> {
+    /// The number of entries with each status.
+    pub status_counts: StatusCounts,
+    /// The mean score of the entries that have been scored.
+    /// This is `None` when no entry has been scored.
+    pub mean_score: Option<f32>,
+    /// The median score of the entries that have been scored.
+    /// This is `None` when no entry has been scored.
+    pub median_score: Option<f32>,
+    /// The number of entries with each score, indexed by the score.
+    /// Entries that haven't been scored are counted at index 0.
+    pub score_distribution: [u32; 11],
+    /// The number of entries of each series type.
+    pub series_types: HashMap<T, u32>,
+    /// The number of completed entries that were finished in each year.
+    /// Completed entries without a finish date aren't counted.
+    pub completed_per_year: BTreeMap<i32, u32>,
+    /// The total number of episodes watched / chapters read across every entry.
+    /// Rewatches / rereads aren't counted.
+    pub total_progress: u32,
+    /// The fraction of entries that were started and have been completed, from 0.0 to 1.0.
+    /// Entries that are planning to be watched / read aren't counted as started.
+    /// This is `None` when no entry has been started.
+    pub completion_rate: Option<f32>,
+    /// Every status whose number of entries on the list doesn't match the number MyAnimeList
+    /// reported with it.
+    pub mismatches: Vec<StatusMismatch>,
+}
+
+impl<T: Eq + Hash> ListStats<T> {
+    /// Returns true if the number of entries with each status matches the numbers
+    /// MyAnimeList reported.
+    #[inline]
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl<E: ListEntry> ListEntries<E> {
+    /// Computes statistics from the list's entries, and compares the number of entries with each
+    /// status against the numbers in the list's [`user_info`](#structfield.user_info).
+    pub fn stats(&self) -> ListStats<E::SeriesType> {
+        let mut status_counts = StatusCounts::default();
+        let mut score_distribution = [0; 11];
+        let mut series_types = HashMap::new();
+        let mut completed_per_year = BTreeMap::new();
+        let mut total_progress = 0;
+        let mut scores = Vec::new();
+
+        for entry in &self.entries {
+            let summary = entry.summary();
+
+            status_counts.increment(summary.status);
+            total_progress += summary.progress;
+            *series_types.entry(entry.series_type()).or_insert(0) += 1;
+
+            if let Some(count) = score_distribution.get_mut(summary.score as usize) {
+                *count += 1;
+            }
+
+            if summary.score > 0 {
+                scores.push(summary.score);
+            }
+
+            if summary.status == Status::Completed {
+                if let Some(date) = summary.finish_date {
+                    *completed_per_year.entry(date.year()).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let started = status_counts.total() - status_counts.plan_to_watch_or_read;
+
+        let completion_rate = if started > 0 {
+            Some(status_counts.completed as f32 / started as f32)
+        } else {
+            None
+        };
+
+        let server_counts = self.user_info.status_counts();
+
+        let mismatches = STATUSES
+            .iter()
+            .filter(|&&status| status_counts.get(status) != server_counts.get(status))
+            .map(|&status| StatusMismatch {
+                status,
+                local: status_counts.get(status),
+                server: server_counts.get(status),
+            })
+            .collect();
+
+        ListStats {
+            status_counts,
+            mean_score: mean(&scores),
+            median_score: median(&mut scores),
+            score_distribution,
+            series_types,
+            completed_per_year,
+            total_progress,
+            completion_rate,
+            mismatches,
+        }
+    }
+}
+
+fn mean(scores: &[u8]) -> Option<f32> {
+    if scores.is_empty() {
+        return None;
+    }
+
+    let sum = scores.iter().map(|&score| u32::from(score)).sum::<u32>();
+    Some(sum as f32 / scores.len() as f32)
+}
+
+fn median(scores: &mut [u8]) -> Option<f32> {
+    if scores.is_empty() {
+        return None;
+    }
+
+    scores.sort();
+    let mid = scores.len() / 2;
+
+    if scores.len() % 2 == 0 {
+        Some((f32::from(scores[mid - 1]) + f32::from(scores[mid])) / 2.0)
+    } else {
+        Some(f32::from(scores[mid]))
+    }
+}
@@ -0,0 +1,222 @@
+//! This module allows a user's list to be brought to a desired state with as few requests
+//! as possible.
+//!
+//! A [`SyncPlan`] is created by comparing the desired state of a list with the current state
+//! of the list on MyAnimeList, and contains every add, update, and delete request needed to make
+//! the two match. Plans can be inspected before being executed, which makes it easy to perform a
+//! dry run.
+//!
+//! [`SyncPlan`]: ./struct.SyncPlan.html
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use mal::MAL;
+//! use mal::list::Status;
+//! use mal::list::anime::AnimeValues;
+//! use mal::list::sync::SyncMode;
+//!
+//! let mal = MAL::new("username", "password");
+//!
+//! let mut toradora = AnimeValues::new();
+//! toradora.set_watched_episodes(25).set_status(Status::Completed);
+//!
+//! // Make the user's list contain only Toradora, without sending anything yet.
+//! // Its score, tags, and other fields that weren't set are left as they are.
+//! let plan = mal.anime_list().sync(vec![(4224, toradora)], SyncMode::DryRun).unwrap();
+//!
+//! for action in &plan.actions {
+//!     println!("{}", action);
+//! }
+//!
+//! // Send the requests
+//! plan.execute(&mal.anime_list()).unwrap();
+//! ```
+
+use error::MALError;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use super::{EntryValues, List, ListEntry};
+
+/// Determines whether or not a sync should send requests to MyAnimeList.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SyncMode {
+    /// Only return the requests that would be sent.
+    DryRun,
+    /// Send the requests and return them.
+    Execute,
+}
+
+/// Represents a single request that is part of a [`SyncPlan`].
+///
+/// Only the values that need to be sent are marked as changed on the values of add and
+/// update actions.
+///
+/// [`SyncPlan`]: ./struct.SyncPlan.html
+#[derive(Debug, Clone)]
+pub enum SyncAction<V: EntryValues> {
+    /// Adds the series with the specified ID to the user's list.
+    Add(u32, V),
+    /// Updates the series with the specified ID on the user's list.
+    Update(u32, V),
+    /// Removes the series with the specified ID from the user's list.
+    Delete(u32),
+}
+
+impl<V: EntryValues> SyncAction<V> {
+    /// Returns the ID of the series the action operates on.
+    #[inline]
+    pub fn id(&self) -> u32 {
+        match *self {
+            SyncAction::Add(id, _) | SyncAction::Update(id, _) | SyncAction::Delete(id) => id,
+        }
+    }
+}
+
+impl<V: EntryValues> fmt::Display for SyncAction<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SyncAction::Add(id, ref values) => {
+                write!(f, "add {} ({})", id, values.changed_field_names().join(", "))
+            }
+            SyncAction::Update(id, ref values) => {
+                write!(f, "update {} ({})", id, values.changed_field_names().join(", "))
+            }
+            SyncAction::Delete(id) => write!(f, "delete {}", id),
+        }
+    }
+}
+
+/// Contains the requests needed to bring a user's list to a desired state.
+#[derive(Debug, Clone)]
+pub struct SyncPlan<V: EntryValues> {
+    /// The requests to send, in the order they will be sent.
+    pub actions: Vec<SyncAction<V>>,
+}
+
+impl<V: EntryValues + Clone> SyncPlan<V> {
+    /// Returns true if the list is already in the desired state.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Sends every request in the plan with the specified [`List`].
+    ///
+    /// Requests are sent in order, and no more requests will be sent after one fails.
+    /// Planning the sync again will only return the requests that still need to be sent.
+    ///
+    /// [`List`]: ../struct.List.html
+    pub fn execute<E>(&self, list: &List<E>) -> Result<(), MALError>
+    where
+        E: ListEntry<Values = V>,
+    {
+        for action in &self.actions {
+            match *action {
+                SyncAction::Add(id, ref values) => list.add_id(id, &mut values.clone())?,
+                SyncAction::Update(id, ref values) => list.update_id(id, &mut values.clone())?,
+                SyncAction::Delete(id) => list.delete_id(id)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, E> List<'a, E>
+where
+    E: ListEntry,
+    E::Values: Clone,
+{
+    /// Compares the desired state of the user's list with its current state on MyAnimeList,
+    /// and returns the requests needed to make them match without sending them.
+    ///
+    /// `target` should contain the ID and values of every series that should be on the user's
+    /// list. Series on the user's list that aren't in `target` will be deleted, and series
+    /// that are in `target` but aren't on the user's list will be added. If an ID appears
+    /// more than once, the last values given for it are used.
+    ///
+    /// Only the fields marked as changed on the values in `target`, such as by calling their
+    /// setters, are compared and sent. Every other field is left as it is on MyAnimeList, so
+    /// setting only the progress of a series won't reset its score or tags.
+    ///
+    /// The current state of the list is always requested from MyAnimeList, even when the
+    /// [`MAL`] instance has a cache, so changes made on the website since the list was cached
+    /// aren't overwritten.
+    ///
+    /// [`MAL`]: ../struct.MAL.html
+    pub fn plan_sync<I>(&self, target: I) -> Result<SyncPlan<E::Values>, MALError>
+    where
+        I: IntoIterator<Item = (u32, E::Values)>,
+    {
+        let remote = self.request_list()?.entries;
+
+        let remote_ids = remote
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.id(), i))
+            .collect::<HashMap<_, _>>();
+
+        let mut target_ids: HashMap<u32, usize> = HashMap::new();
+        let mut targets: Vec<(u32, E::Values)> = Vec::new();
+
+        for (id, values) in target {
+            match target_ids.get(&id) {
+                Some(&i) => targets[i].1 = values,
+                None => {
+                    target_ids.insert(id, targets.len());
+                    targets.push((id, values));
+                }
+            }
+        }
+
+        let mut actions = Vec::new();
+
+        for (id, mut values) in targets {
+            match remote_ids.get(&id) {
+                Some(&i) => {
+                    if values.retain_differences(remote[i].values()) {
+                        actions.push(SyncAction::Update(id, values));
+                    }
+                }
+                None => {
+                    // The status is always sent so MAL doesn't pick one for us
+                    values.mark_field_changed("status");
+
+                    actions.push(SyncAction::Add(id, values));
+                }
+            }
+        }
+
+        let target_ids = target_ids.keys().collect::<HashSet<_>>();
+
+        for entry in &remote {
+            if !target_ids.contains(&entry.id()) {
+                actions.push(SyncAction::Delete(entry.id()));
+            }
+        }
+
+        Ok(SyncPlan { actions })
+    }
+
+    /// Brings the user's list to the desired state in a single call.
+    ///
+    /// This is the same as calling [`plan_sync`], and then calling [`SyncPlan::execute`]
+    /// on the result if `mode` is [`SyncMode::Execute`]. The plan is returned either way.
+    ///
+    /// [`plan_sync`]: #method.plan_sync
+    /// [`SyncPlan::execute`]: ./sync/struct.SyncPlan.html#method.execute
+    /// [`SyncMode::Execute`]: ./sync/enum.SyncMode.html#variant.Execute
+    pub fn sync<I>(&self, target: I, mode: SyncMode) -> Result<SyncPlan<E::Values>, MALError>
+    where
+        I: IntoIterator<Item = (u32, E::Values)>,
+    {
+        let plan = self.plan_sync(target)?;
+
+        if mode == SyncMode::Execute {
+            plan.execute(self)?;
+        }
+
+        Ok(plan)
+    }
+}
@@ -0,0 +1,382 @@
+//! This module provides an asynchronous version of the API, built on top of futures.
+//!
+//! It mirrors the [`MAL`] and [`List`] structs with [`AsyncMAL`] and [`AsyncList`], whose methods
+//! return futures instead of blocking the current thread.
+//!
+//! Requests sent through this module still go through the [`RateLimiter`] and [`BanTracker`] of the
+//! underlying [`MAL`] instance. However, since waiting for the rate limiter would block the event loop,
+//! a [`RequestError::RateLimitExceeded`] error is always returned when the rate limit would be exceeded,
+//! and failed requests are never retried.
+//!
+//! Refreshing an access token would also block the event loop, so the access token of an [`OAuth`]
+//! instance is never refreshed automatically. Call [`OAuth::refresh_if_needed`] yourself instead.
+//!
+//! This module is only available with the `async` feature.
+//!
+//! [`MAL`]: ../struct.MAL.html
+//! [`List`]: ../list/struct.List.html
+//! [`AsyncMAL`]: ./struct.AsyncMAL.html
+//! [`AsyncList`]: ./struct.AsyncList.html
+//! [`RateLimiter`]: ../rate_limit/struct.RateLimiter.html
+//! [`BanTracker`]: ../rate_limit/struct.BanTracker.html
+//! [`RequestError::RateLimitExceeded`]: ../error/enum.RequestError.html#variant.RateLimitExceeded
+//! [`OAuth`]: ../auth/struct.OAuth.html
+//! [`OAuth::refresh_if_needed`]: ../auth/struct.OAuth.html#method.refresh_if_needed
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate futures;
+//! extern crate mal;
+//! extern crate reqwest;
+//!
+//! use futures::{future, Future};
+//! use mal::MAL;
+//! use mal::error::RequestError;
+//! use mal::nonblocking::{AsyncMAL, AsyncTransport};
+//! use mal::transport::{HttpRequest, HttpResponse};
+//! use reqwest::StatusCode;
+//!
+//! #[derive(Debug)]
+//! struct FakeTransport;
+//!
+//! impl AsyncTransport for FakeTransport {
+//!     fn send(&self, _: HttpRequest) -> Box<dyn Future<Item = HttpResponse, Error = RequestError>> {
+//!         Box::new(future::ok(HttpResponse::new(StatusCode::Unauthorized, "")))
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let mal = AsyncMAL::with_transport(MAL::new("username", "password"), FakeTransport);
+//! let valid = mal.verify_credentials().wait().unwrap();
+//!
+//! assert_eq!(valid, false);
+//! # }
+//! ```
+
+use error::{MALError, RequestError};
+use futures::{future, Future, Stream};
+use futures::future::{Either, Loop};
+use list::{self, EntryValues, ListEntries, ListEntry};
+use {Api, MAL};
+use request::{self, Request};
+use reqwest::StatusCode;
+use reqwest::unstable::async::{Client, Decoder};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::mem;
+use std::rc::Rc;
+use tokio_core::reactor::Handle;
+use transport::{HttpRequest, HttpResponse};
+use v2;
+
+#[cfg(feature = "anime")]
+use list::anime::AnimeEntry;
+#[cfg(feature = "manga")]
+use list::manga::MangaEntry;
+
+/// A future that resolves to either `T` or a [`MALError`].
+///
+/// [`MALError`]: ../error/enum.MALError.html
+pub type MALFuture<'a, T> = Box<dyn Future<Item = T, Error = MALError> + 'a>;
+
+/// Used to send HTTP requests to MyAnimeList asynchronously.
+///
+/// This is the asynchronous counterpart of the [`Transport`] trait.
+///
+/// [`Transport`]: ../transport/trait.Transport.html
+pub trait AsyncTransport: Debug {
+    /// Sends the `request` and returns a future that resolves to the response that was received.
+    fn send(
+        &self,
+        request: HttpRequest,
+    ) -> Box<dyn Future<Item = HttpResponse, Error = RequestError>>;
+}
+
+/// The default [`AsyncTransport`], which sends requests with an asynchronous reqwest [`Client`].
+///
+/// [`AsyncTransport`]: ./trait.AsyncTransport.html
+/// [`Client`]: ../../reqwest/unstable/async/struct.Client.html
+#[derive(Debug, Clone)]
+pub struct ReqwestAsyncTransport {
+    /// The client used to send requests to the API.
+    pub client: Client,
+}
+
+impl ReqwestAsyncTransport {
+    /// Creates a new `ReqwestAsyncTransport` that will send requests on the event loop
+    /// the `handle` belongs to.
+    #[inline]
+    pub fn new(handle: &Handle) -> ReqwestAsyncTransport {
+        ReqwestAsyncTransport {
+            client: Client::new(handle),
+        }
+    }
+}
+
+impl AsyncTransport for ReqwestAsyncTransport {
+    fn send(
+        &self,
+        request: HttpRequest,
+    ) -> Box<dyn Future<Item = HttpResponse, Error = RequestError>> {
+        let mut builder = self.client.request(request.method, request.url);
+        builder.headers(request.headers);
+
+        if let Some(body) = request.body {
+            builder.body(body);
+        }
+
+        let resp = builder
+            .send()
+            .map_err(RequestError::HttpError)
+            .and_then(|mut resp| {
+                let status = resp.status();
+                let headers = resp.headers().clone();
+                let body = mem::replace(resp.body_mut(), Decoder::empty());
+
+                body.concat2()
+                    .map_err(RequestError::ReadResponse)
+                    .map(move |body| HttpResponse {
+                        status,
+                        headers,
+                        body: String::from_utf8_lossy(&body).into_owned(),
+                    })
+            });
+
+        Box::new(resp)
+    }
+}
+
+/// Used to interact with the MyAnimeList API asynchronously.
+///
+/// The wrapped [`MAL`] instance provides the user credentials, rate limiter, and ban tracker
+/// that are used for every request.
+///
+/// [`MAL`]: ../struct.MAL.html
+#[derive(Debug, Clone)]
+pub struct AsyncMAL<'a> {
+    /// The [`MAL`] instance that provides the user credentials and request settings.
+    ///
+    /// [`MAL`]: ../struct.MAL.html
+    pub mal: MAL<'a>,
+    /// The transport used to send requests to the API.
+    pub transport: Rc<dyn AsyncTransport + 'a>,
+}
+
+impl<'a> AsyncMAL<'a> {
+    /// Creates a new `AsyncMAL` instance that will send requests on the event loop
+    /// the `handle` belongs to.
+    #[inline]
+    pub fn new(mal: MAL<'a>, handle: &Handle) -> AsyncMAL<'a> {
+        AsyncMAL::with_transport(mal, ReqwestAsyncTransport::new(handle))
+    }
+
+    /// Creates a new `AsyncMAL` instance that will send all requests through the
+    /// specified [`AsyncTransport`].
+    ///
+    /// [`AsyncTransport`]: ./trait.AsyncTransport.html
+    #[inline]
+    pub fn with_transport<T>(mal: MAL<'a>, transport: T) -> AsyncMAL<'a>
+    where
+        T: AsyncTransport + 'a,
+    {
+        AsyncMAL {
+            mal,
+            transport: Rc::new(transport),
+        }
+    }
+
+    /// Returns a new [`AsyncList`] instance to perform anime list operations.
+    ///
+    /// [`AsyncList`]: ./struct.AsyncList.html
+    #[cfg(feature = "anime")]
+    #[inline]
+    pub fn anime_list(&self) -> AsyncList<AnimeEntry> {
+        AsyncList::<AnimeEntry>::new(self)
+    }
+
+    /// Returns a new [`AsyncList`] instance to perform manga list operations.
+    ///
+    /// [`AsyncList`]: ./struct.AsyncList.html
+    #[cfg(feature = "manga")]
+    #[inline]
+    pub fn manga_list(&self) -> AsyncList<MangaEntry> {
+        AsyncList::<MangaEntry>::new(self)
+    }
+
+    /// Returns a future that resolves to true if the provided account credentials are correct.
+    pub fn verify_credentials(&self) -> MALFuture<'a, bool> {
+        let resp = self.send(&Request::VerifyCredentials).then(|result| match result {
+            Ok(_) => Ok(true),
+            Err(RequestError::BadResponseCode(StatusCode::Unauthorized)) => Ok(false),
+            Err(err) => Err(MALError::Request(err)),
+        });
+
+        Box::new(resp)
+    }
+
+    fn send(&self, req: &Request) -> Box<dyn Future<Item = String, Error = RequestError>> {
+        if let Err(err) = self.mal.ban_tracker.check() {
+            return Box::new(future::err(err));
+        }
+
+        if let Some(ref limiter) = self.mal.rate_limiter {
+            if let Err(wait) = limiter.try_acquire() {
+                return Box::new(future::err(RequestError::RateLimitExceeded(wait)));
+            }
+        }
+
+        let api = self.mal.api.clone();
+        let bans = self.mal.ban_tracker.clone();
+
+        let resp = self.transport
+            .send(req.build(&self.mal))
+            .and_then(move |resp| request::check_response(resp, &api, &bans))
+            .map(|resp| resp.body);
+
+        Box::new(resp)
+    }
+}
+
+/// The asynchronous counterpart of the [`List`] struct.
+///
+/// [`List`]: ../list/struct.List.html
+#[derive(Debug, Clone, Copy)]
+pub struct AsyncList<'a, E: ListEntry> {
+    /// A reference to the [`AsyncMAL`] instance used to perform operations on a user's list.
+    ///
+    /// [`AsyncMAL`]: ./struct.AsyncMAL.html
+    pub mal: &'a AsyncMAL<'a>,
+    _list_entry: PhantomData<E>,
+}
+
+impl<'a, E: ListEntry + 'a> AsyncList<'a, E> {
+    /// Creates a new [`AsyncList`] instance for performing operations on a user's list.
+    ///
+    /// [`AsyncList`]: ./struct.AsyncList.html
+    #[inline]
+    pub fn new(mal: &'a AsyncMAL<'a>) -> AsyncList<'a, E> {
+        AsyncList {
+            mal,
+            _list_entry: PhantomData,
+        }
+    }
+
+    /// Searches MyAnimeList for the type of series defined by the [`AsyncList`] instance
+    /// and returns a future that resolves to all found results.
+    ///
+    /// [`AsyncList`]: ./struct.AsyncList.html
+    pub fn search_for<S>(&self, name: S) -> MALFuture<'a, Vec<E::Info>>
+    where
+        S: AsRef<str>,
+    {
+        let resp = self.mal
+            .send(&Request::Search(name.as_ref(), E::list_type()));
+
+        match self.mal.mal.api {
+            Api::Legacy => Box::new(resp.then(list::parse_search_results::<E>)),
+            Api::V2 { .. } => Box::new(resp.then(v2::parse_search_results::<E>)),
+        }
+    }
+
+    /// Returns a future that resolves to all entries on a user's list.
+    pub fn read(&self) -> MALFuture<'a, ListEntries<E>> {
+        let mal = self.mal;
+
+        let resp = mal.send(&Request::List(&mal.mal.username, E::list_type()))
+            .map_err(MALError::Request);
+
+        if let Api::Legacy = mal.mal.api {
+            return Box::new(resp.and_then(|resp| list::parse_list(&resp)));
+        }
+
+        // Version 2 of the API splits the list into pages that have to be requested one at a time
+        let entries = resp.and_then(move |resp| {
+            future::loop_fn((resp, Vec::new()), move |(resp, mut entries)| {
+                let (page, next) = match v2::parse_list_page::<E>(&resp) {
+                    Ok(page) => page,
+                    Err(err) => return Either::A(future::err(err)),
+                };
+
+                entries.extend(page);
+
+                match next {
+                    Some(url) => Either::B(
+                        mal.send(&Request::Next(&url))
+                            .map_err(MALError::Request)
+                            .map(move |resp| Loop::Continue((resp, entries))),
+                    ),
+                    None => Either::A(future::ok(Loop::Break(entries))),
+                }
+            })
+        });
+
+        let list = entries.map(|entries| ListEntries {
+            user_info: E::user_info_from_entries(&entries),
+            entries,
+        });
+
+        Box::new(list)
+    }
+
+    /// Returns a future that adds an entry to a user's list by id.
+    ///
+    /// If the entry is already on a user's list, nothing will happen.
+    pub fn add_id<'b>(&self, id: u32, values: &'b mut E::Values) -> MALFuture<'b, ()>
+    where
+        'a: 'b,
+    {
+        let body = match values
+            .validate()
+            .and_then(|_| values.generate_body(&self.mal.mal.api))
+        {
+            Ok(body) => body,
+            Err(err) => return Box::new(future::err(MALError::List(err))),
+        };
+
+        let resp = self.mal
+            .send(&Request::Add(id, E::list_type(), &body))
+            .map_err(MALError::Request)
+            .map(move |_| values.reset_changed_fields());
+
+        Box::new(resp)
+    }
+
+    /// Returns a future that updates an entry on a user's list by id.
+    ///
+    /// If the entry isn't already on the user's list, nothing will happen.
+    pub fn update_id<'b>(&self, id: u32, values: &'b mut E::Values) -> MALFuture<'b, ()>
+    where
+        'a: 'b,
+    {
+        let body = match values
+            .validate()
+            .and_then(|_| values.generate_body(&self.mal.mal.api))
+        {
+            Ok(body) => body,
+            Err(err) => return Box::new(future::err(MALError::List(err))),
+        };
+
+        let resp = self.mal
+            .send(&Request::Update(id, E::list_type(), &body))
+            .map_err(MALError::Request)
+            .map(move |_| values.reset_changed_fields());
+
+        Box::new(resp)
+    }
+
+    /// Returns a future that removes an entry from a user's list by its id.
+    ///
+    /// If the entry isn't already on a user's list, nothing will happen.
+    pub fn delete_id(&self, id: u32) -> MALFuture<'a, ()> {
+        let resp = self.mal
+            .send(&Request::Delete(id, E::list_type()))
+            .then(|result| match result {
+                // Version 2 of the API returns a 404 when the entry isn't on the user's list
+                Ok(_) | Err(RequestError::BadResponseCode(StatusCode::NotFound)) => Ok(()),
+                Err(err) => Err(MALError::Request(err)),
+            });
+
+        Box::new(resp)
+    }
+}
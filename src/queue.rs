@@ -0,0 +1,481 @@
+//! This module provides a durable queue for list updates that couldn't be sent to MyAnimeList,
+//! so they can be sent later instead of being lost.
+//!
+//! When the `write_queue` field of a [`MAL`] instance is set, [`List::update_id`] and
+//! [`List::update`] will journal every update that couldn't be sent for a temporary reason, such
+//! as when MyAnimeList couldn't be reached, is having problems, or is rate limiting / banning the
+//! caller. Updates that MyAnimeList rejected, or that failed because the caller couldn't be
+//! authenticated, are returned as errors instead. Updates can also be queued directly with
+//! [`WriteQueue::push`] to work offline.
+//!
+//! The journal is a file with one JSON record per line, containing the list type, the ID of
+//! the series, and the request body generated for the update. Multiple updates to the same
+//! series are merged together, with the values of later updates replacing those of earlier
+//! ones. Pending updates are sent in the order they were first queued with [`WriteQueue::flush`].
+//!
+//! [`MAL`]: ../struct.MAL.html
+//! [`List::update_id`]: ../list/struct.List.html#method.update_id
+//! [`List::update`]: ../list/struct.List.html#method.update
+//! [`WriteQueue::push`]: ./struct.WriteQueue.html#method.push
+//! [`WriteQueue::flush`]: ./struct.WriteQueue.html#method.flush
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use mal::MAL;
+//! use mal::list::anime::AnimeValues;
+//! use mal::queue::WriteQueue;
+//!
+//! let mut mal = MAL::new("username", "password");
+//! mal.write_queue = Some(WriteQueue::new("/tmp/mal-queue.jsonl"));
+//!
+//! let mut values = AnimeValues::new();
+//! values.set_watched_episodes(5);
+//!
+//! // If MyAnimeList is down, the update will be journaled instead of failing
+//! mal.anime_list().update_id(4224, &mut values).unwrap();
+//!
+//! // Later on, send everything that's still pending
+//! let report = mal.write_queue.as_ref().unwrap().flush(&mal).unwrap();
+//!
+//! for update in &report.sent {
+//!     println!("updated {}", update.id);
+//! }
+//!
+//! for &(ref update, ref err) in &report.rejected {
+//!     println!("MyAnimeList rejected the update to {}: {}", update.id, err);
+//! }
+//! ```
+
+use chrono::{DateTime, TimeZone, Utc};
+use error::{AuthError, QueueError, RequestError};
+use list::{EntryValues, List, ListEntry, ListType};
+use minidom::Element;
+use request::Request;
+use reqwest::StatusCode;
+use serde_json::{self, Value};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use url::form_urlencoded;
+use v2;
+use {Api, MAL};
+
+/// A queue of list updates that is journaled to a file.
+///
+/// Cloning a `WriteQueue` will return a handle to the same journal.
+#[derive(Debug, Clone)]
+pub struct WriteQueue {
+    path: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl WriteQueue {
+    /// Creates a new `WriteQueue` that journals updates to the specified file.
+    ///
+    /// Updates that are already in the file will be sent when the queue is flushed.
+    /// The file will be created when the first update is queued.
+    pub fn new<P: Into<PathBuf>>(path: P) -> WriteQueue {
+        WriteQueue {
+            path: path.into(),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Returns the path of the journal.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queues an update to the series with the specified ID on the user's list,
+    /// without trying to send it first.
+    ///
+    /// Only the values that are marked as changed are queued, and they are marked as unchanged
+    /// once they have been journaled.
+    pub fn push<E: ListEntry>(
+        &self,
+        list: &List<E>,
+        id: u32,
+        values: &mut E::Values,
+    ) -> Result<(), QueueError> {
+        let body = values
+            .generate_body(&list.mal.api)
+            .map_err(QueueError::List)?;
+
+        self.push_body(E::list_type(), id, &list.mal.api, body)?;
+        values.reset_changed_fields();
+        Ok(())
+    }
+
+    pub(crate) fn push_body(
+        &self,
+        list_type: ListType,
+        id: u32,
+        api: &Api,
+        body: String,
+    ) -> Result<(), QueueError> {
+        let update = PendingUpdate {
+            list_type,
+            id,
+            format: BodyFormat::from_api(api),
+            body,
+            queued_at: Utc::now(),
+        };
+
+        let mut line = serde_json::to_string(&update.to_json()).map_err(QueueError::Json)?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap();
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(QueueError::Io)?;
+
+        // A single write keeps a crash from leaving anything but an unterminated last line behind
+        file.write_all(line.as_bytes()).map_err(QueueError::Io)?;
+        file.sync_data().map_err(QueueError::Io)
+    }
+
+    /// Returns every update that hasn't been sent yet, in the order they will be sent.
+    ///
+    /// Updates to the same series are merged into one.
+    pub fn pending(&self) -> Result<Vec<PendingUpdate>, QueueError> {
+        let _guard = self.lock.lock().unwrap();
+        let lines = self.read_lines()?;
+
+        coalesce(&lines)
+    }
+
+    /// Returns true if there are no updates waiting to be sent.
+    #[inline]
+    pub fn is_empty(&self) -> Result<bool, QueueError> {
+        self.pending().map(|pending| pending.is_empty())
+    }
+
+    /// Sends every pending update with the specified [`MAL`] instance.
+    ///
+    /// Updates are removed from the queue once MyAnimeList either accepts them or rejects them
+    /// with a client error. If an update can't be sent for any other reason, such as MyAnimeList
+    /// being unreachable again or the access token failing to refresh, it and every update after
+    /// it are kept in the queue.
+    ///
+    /// Updates that were queued through a different version of the API than the one `mal` uses
+    /// are skipped and kept in the queue.
+    ///
+    /// [`MAL`]: ../struct.MAL.html
+    pub fn flush(&self, mal: &MAL) -> Result<FlushReport, QueueError> {
+        let (pending, num_lines) = {
+            let _guard = self.lock.lock().unwrap();
+            let lines = self.read_lines()?;
+            (coalesce(&lines)?, lines.len())
+        };
+
+        let format = BodyFormat::from_api(&mal.api);
+
+        let mut report = FlushReport {
+            sent: Vec::new(),
+            rejected: Vec::new(),
+            remaining: Vec::new(),
+        };
+
+        let mut pending = pending.into_iter();
+
+        while let Some(update) = pending.next() {
+            if update.format != format {
+                report.remaining.push(update);
+                continue;
+            }
+
+            match Request::Update(update.id, update.list_type, &update.body).send(mal) {
+                Ok(_) => report.sent.push(update),
+                Err(ref err) if is_undeliverable(err) => {
+                    report.remaining.push(update);
+                    report.remaining.extend(pending);
+                    break;
+                }
+                Err(err) => report.rejected.push((update, err)),
+            }
+        }
+
+        let _guard = self.lock.lock().unwrap();
+
+        // Updates may have been queued while the others were being sent
+        let new_lines = self.read_lines()?.into_iter().skip(num_lines);
+
+        let mut journal = String::new();
+
+        for update in &report.remaining {
+            journal += &serde_json::to_string(&update.to_json()).map_err(QueueError::Json)?;
+            journal.push('\n');
+        }
+
+        for line in new_lines {
+            journal += &line;
+            journal.push('\n');
+        }
+
+        self.write_journal(&journal)?;
+        Ok(report)
+    }
+
+    // Returns every complete line in the journal
+    fn read_lines(&self) -> Result<Vec<String>, QueueError> {
+        let mut contents = String::new();
+
+        match File::open(&self.path) {
+            Ok(mut file) => {
+                file.read_to_string(&mut contents)
+                    .map_err(QueueError::Io)?;
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(QueueError::Io(err)),
+        }
+
+        let mut lines = contents.split('\n').map(String::from).collect::<Vec<_>>();
+
+        // The last line is either empty or was never finished being written
+        lines.pop();
+        Ok(lines)
+    }
+
+    // Writes to a temporary file first so a failed write can't lose any queued updates
+    fn write_journal(&self, journal: &str) -> Result<(), QueueError> {
+        let tmp_path = self.path.with_extension("tmp");
+
+        let result = File::create(&tmp_path)
+            .and_then(|mut file| {
+                file.write_all(journal.as_bytes())?;
+                file.sync_data()
+            })
+            .and_then(|_| fs::rename(&tmp_path, &self.path));
+
+        if result.is_err() {
+            fs::remove_file(&tmp_path).ok();
+        }
+
+        result.map_err(QueueError::Io)
+    }
+}
+
+/// An update that is waiting to be sent to MyAnimeList.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingUpdate {
+    /// The type of list the update is for.
+    pub list_type: ListType,
+    /// The ID of the series to update.
+    pub id: u32,
+    format: BodyFormat,
+    /// The request body that will be sent.
+    pub body: String,
+    /// The last time an update to the series was queued.
+    pub queued_at: DateTime<Utc>,
+}
+
+impl PendingUpdate {
+    fn from_json(json: &Value) -> Option<PendingUpdate> {
+        let list_type = match json.get("list_type")?.as_str()? {
+            "anime" => ListType::Anime,
+            "manga" => ListType::Manga,
+            _ => return None,
+        };
+
+        let format = match json.get("format")?.as_str()? {
+            "xml" => BodyFormat::Xml,
+            "form" => BodyFormat::Form,
+            _ => return None,
+        };
+
+        let id = json.get("id")?.as_u64()?;
+        let queued_at = json.get("queued_at")?.as_i64()?;
+
+        Some(PendingUpdate {
+            list_type,
+            id: id as u32,
+            format,
+            body: json.get("body")?.as_str()?.into(),
+            queued_at: Utc.timestamp(queued_at, 0),
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        let list_type = match self.list_type {
+            ListType::Anime => "anime",
+            ListType::Manga => "manga",
+        };
+
+        let format = match self.format {
+            BodyFormat::Xml => "xml",
+            BodyFormat::Form => "form",
+        };
+
+        json!({
+            "list_type": list_type,
+            "id": self.id,
+            "format": format,
+            "body": self.body,
+            "queued_at": self.queued_at.timestamp(),
+        })
+    }
+
+    // Combines the fields of both updates, with the fields of `later` taking priority
+    fn merge(&mut self, later: PendingUpdate) -> Option<()> {
+        let fields = merge_fields(self.fields()?, later.fields()?);
+
+        self.body = match self.format {
+            BodyFormat::Xml => {
+                let mut entry = Element::bare("entry");
+
+                for (name, value) in fields {
+                    let mut elem = Element::bare(name);
+                    elem.append_text_node(value);
+                    entry.append_child(elem);
+                }
+
+                let mut buffer = Vec::new();
+                entry.write_to(&mut buffer).ok()?;
+                String::from_utf8(buffer).ok()?
+            }
+            BodyFormat::Form => {
+                let fields = fields
+                    .iter()
+                    .map(|field| (field.0.as_str(), field.1.clone()))
+                    .collect::<Vec<_>>();
+
+                v2::encode_form(&fields)
+            }
+        };
+
+        self.queued_at = later.queued_at;
+        Some(())
+    }
+
+    fn fields(&self) -> Option<Vec<(String, String)>> {
+        match self.format {
+            BodyFormat::Xml => {
+                let entry = self.body.parse::<Element>().ok()?;
+
+                let fields = entry
+                    .children()
+                    .map(|child| (child.name().to_string(), child.text()))
+                    .collect();
+
+                Some(fields)
+            }
+            BodyFormat::Form => {
+                let fields = form_urlencoded::parse(self.body.as_bytes())
+                    .into_owned()
+                    .collect();
+
+                Some(fields)
+            }
+        }
+    }
+}
+
+/// Contains the results of flushing a [`WriteQueue`].
+///
+/// [`WriteQueue`]: ./struct.WriteQueue.html
+#[derive(Debug)]
+pub struct FlushReport {
+    /// The updates MyAnimeList accepted.
+    pub sent: Vec<PendingUpdate>,
+    /// The updates MyAnimeList rejected, along with the error it responded with.
+    /// These are removed from the queue, as sending them again would fail the same way.
+    pub rejected: Vec<(PendingUpdate, RequestError)>,
+    /// The updates that are still in the queue.
+    pub remaining: Vec<PendingUpdate>,
+}
+
+impl FlushReport {
+    /// Returns true if every update was removed from the queue.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.remaining.is_empty()
+    }
+}
+
+// The body of an update depends on the version of the API it was generated for
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum BodyFormat {
+    Xml,
+    Form,
+}
+
+impl BodyFormat {
+    fn from_api(api: &Api) -> BodyFormat {
+        match *api {
+            Api::Legacy => BodyFormat::Xml,
+            Api::V2 { .. } => BodyFormat::Form,
+        }
+    }
+}
+
+/// Returns true if the specified error means the update couldn't be delivered for a reason
+/// that should go away on its own, so it should be queued instead of discarded.
+///
+/// Authentication failures, such as a wrong password or a refresh token that was refused,
+/// aren't queued since sending the update again would keep failing until the caller fixes them.
+pub(crate) fn is_undeliverable(err: &RequestError) -> bool {
+    match *err {
+        RequestError::HttpError(_)
+        | RequestError::ReadResponse(_)
+        | RequestError::RateLimitExceeded(_)
+        | RequestError::RateLimited(_)
+        | RequestError::Banned(_) => true,
+        RequestError::BadResponseCode(status) => match status {
+            StatusCode::RequestTimeout | StatusCode::TooManyRequests => true,
+            status => status.is_server_error(),
+        },
+        // The token endpoint being unreachable doesn't mean the refresh token is invalid
+        RequestError::TokenRefresh(ref err) => match **err {
+            AuthError::Request(ref err) => is_undeliverable(err),
+            _ => false,
+        },
+    }
+}
+
+fn coalesce(lines: &[String]) -> Result<Vec<PendingUpdate>, QueueError> {
+    let mut pending: Vec<PendingUpdate> = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let update = serde_json::from_str(line)
+            .ok()
+            .and_then(|json| PendingUpdate::from_json(&json))
+            .ok_or_else(|| QueueError::MalformedRecord(i + 1))?;
+
+        let existing = pending.iter_mut().find(|p| {
+            p.list_type == update.list_type && p.id == update.id && p.format == update.format
+        });
+
+        match existing {
+            Some(existing) => existing
+                .merge(update)
+                .ok_or_else(|| QueueError::MalformedRecord(i + 1))?,
+            None => pending.push(update),
+        }
+    }
+
+    Ok(pending)
+}
+
+fn merge_fields(
+    mut fields: Vec<(String, String)>,
+    later: Vec<(String, String)>,
+) -> Vec<(String, String)> {
+    for (name, value) in later {
+        match fields.iter_mut().find(|field| field.0 == name) {
+            Some(field) => field.1 = value,
+            None => fields.push((name, value)),
+        }
+    }
+
+    fields
+}
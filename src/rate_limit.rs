@@ -0,0 +1,273 @@
+//! This module provides a client-side rate limiter to avoid having the caller's IP banned
+//! by MyAnimeList for sending too many requests.
+//!
+//! By default, every [`MAL`] instance will use a [`RateLimiter`] that allows 5 requests per minute
+//! and blocks the current thread until a request can be sent without exceeding that limit.
+//! The limiter is shared between every clone of the [`MAL`] instance it was created with, as well as
+//! every [`List`] created from them.
+//!
+//! If MyAnimeList does end up rejecting requests because of the rate limit, the [`BanTracker`]
+//! of the [`MAL`] instance will remember it so subsequent requests fail locally instead of
+//! extending the ban.
+//!
+//! [`MAL`]: ../struct.MAL.html
+//! [`RateLimiter`]: ./struct.RateLimiter.html
+//! [`BanTracker`]: ./struct.BanTracker.html
+//! [`List`]: ../list/struct.List.html
+
+use error::RequestError;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Determines what happens when sending a request would exceed the rate limit.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LimitBehavior {
+    /// Block the current thread until the request can be sent.
+    Block,
+    /// Return a [`RequestError::RateLimitExceeded`] error without sending the request.
+    ///
+    /// [`RequestError::RateLimitExceeded`]: ../error/enum.RequestError.html#variant.RateLimitExceeded
+    Error,
+}
+
+impl Default for LimitBehavior {
+    #[inline]
+    fn default() -> Self {
+        LimitBehavior::Block
+    }
+}
+
+/// A token bucket rate limiter.
+///
+/// The bucket starts out full, and each request that is sent removes one token from it.
+/// Tokens are then added back to the bucket at a constant rate.
+///
+/// Cloning a `RateLimiter` will return a handle to the same bucket.
+///
+/// # Examples
+///
+/// ```
+/// use mal::rate_limit::{LimitBehavior, RateLimiter};
+/// use std::time::Duration;
+///
+/// // Allow a single request every minute and return an error if it's exceeded
+/// let limiter = RateLimiter::new(1, Duration::from_secs(60))
+///     .with_behavior(LimitBehavior::Error);
+///
+/// assert!(limiter.acquire().is_ok());
+/// assert!(limiter.acquire().is_err());
+///
+/// // Clones use the same bucket
+/// assert!(limiter.clone().acquire().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+    capacity: u32,
+    refill_interval: Duration,
+    behavior: LimitBehavior,
+}
+
+impl RateLimiter {
+    /// Creates a new `RateLimiter` that allows up to `requests` requests to be sent within the
+    /// specified duration.
+    ///
+    /// The limiter will block when the limit would be exceeded.
+    /// To change this, use [`with_behavior`].
+    ///
+    /// [`with_behavior`]: #method.with_behavior
+    pub fn new(requests: u32, per: Duration) -> RateLimiter {
+        let capacity = requests.max(1);
+
+        RateLimiter {
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: f64::from(capacity),
+                last_refill: Instant::now(),
+            })),
+            capacity,
+            refill_interval: per / capacity,
+            behavior: LimitBehavior::default(),
+        }
+    }
+
+    /// Sets what happens when sending a request would exceed the rate limit.
+    #[inline]
+    pub fn with_behavior(mut self, behavior: LimitBehavior) -> RateLimiter {
+        self.behavior = behavior;
+        self
+    }
+
+    /// Returns what happens when sending a request would exceed the rate limit.
+    #[inline]
+    pub fn behavior(&self) -> LimitBehavior {
+        self.behavior
+    }
+
+    /// Takes a token from the bucket if one is available.
+    ///
+    /// If no token is available, the amount of time to wait until one will be is returned.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let interval = duration_to_secs(self.refill_interval);
+
+        let elapsed = duration_to_secs(bucket.last_refill.elapsed());
+        bucket.tokens = (bucket.tokens + elapsed / interval).min(f64::from(self.capacity));
+        bucket.last_refill = Instant::now();
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(secs_to_duration((1.0 - bucket.tokens) * interval))
+        }
+    }
+
+    /// Takes a token from the bucket, which should be done before every request is sent.
+    ///
+    /// If no token is available, this will either block until one is or return a
+    /// [`RequestError::RateLimitExceeded`] error, depending on the limiter's [`LimitBehavior`].
+    ///
+    /// [`RequestError::RateLimitExceeded`]: ../error/enum.RequestError.html#variant.RateLimitExceeded
+    /// [`LimitBehavior`]: ./enum.LimitBehavior.html
+    pub fn acquire(&self) -> Result<(), RequestError> {
+        loop {
+            match self.try_acquire() {
+                Ok(()) => return Ok(()),
+                Err(wait) => match self.behavior {
+                    LimitBehavior::Block => thread::sleep(wait),
+                    LimitBehavior::Error => return Err(RequestError::RateLimitExceeded(wait)),
+                },
+            }
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    /// Creates a new `RateLimiter` that allows 5 requests per minute.
+    #[inline]
+    fn default() -> Self {
+        RateLimiter::new(5, Duration::from_secs(60))
+    }
+}
+
+/// Keeps track of whether or not MyAnimeList is currently rejecting requests from the caller.
+///
+/// Cloning a `BanTracker` will return a handle to the same state.
+///
+/// # Examples
+///
+/// ```
+/// use mal::error::RequestError;
+/// use mal::rate_limit::BanTracker;
+/// use std::time::Duration;
+///
+/// let tracker = BanTracker::new();
+/// assert!(tracker.check().is_ok());
+///
+/// tracker.ban_for(Duration::from_secs(60 * 60));
+///
+/// match tracker.check() {
+///     Err(RequestError::Banned(remaining)) => assert!(remaining.as_secs() <= 60 * 60),
+///     _ => panic!("expected ban"),
+/// }
+///
+/// tracker.clear();
+/// assert!(tracker.check().is_ok());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BanTracker {
+    state: Arc<Mutex<Option<Ban>>>,
+}
+
+impl BanTracker {
+    /// Creates a new `BanTracker` that isn't tracking any ban.
+    #[inline]
+    pub fn new() -> BanTracker {
+        BanTracker::default()
+    }
+
+    /// Remembers that the caller has been banned for the specified duration.
+    #[inline]
+    pub fn ban_for(&self, duration: Duration) {
+        self.set(duration, false);
+    }
+
+    /// Remembers that the caller has been rate limited for the specified duration.
+    #[inline]
+    pub fn rate_limit_for(&self, duration: Duration) {
+        self.set(duration, true);
+    }
+
+    fn set(&self, duration: Duration, rate_limited: bool) {
+        *self.state.lock().unwrap() = Some(Ban {
+            until: Instant::now() + duration,
+            rate_limited,
+        });
+    }
+
+    /// Forgets about any ban that is currently being tracked.
+    #[inline]
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = None;
+    }
+
+    /// Returns the amount of time left until the current ban is estimated to be lifted.
+    pub fn remaining(&self) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+
+        let remaining = match *state {
+            Some(ref ban) => {
+                let now = Instant::now();
+
+                if ban.until > now {
+                    Some(ban.until - now)
+                } else {
+                    None
+                }
+            }
+            None => return None,
+        };
+
+        if remaining.is_none() {
+            *state = None;
+        }
+
+        remaining
+    }
+
+    /// Returns an error if the caller is currently banned or rate limited.
+    pub fn check(&self) -> Result<(), RequestError> {
+        let rate_limited = match *self.state.lock().unwrap() {
+            Some(ref ban) => ban.rate_limited,
+            None => return Ok(()),
+        };
+
+        match self.remaining() {
+            Some(remaining) if rate_limited => Err(RequestError::RateLimited(remaining)),
+            Some(remaining) => Err(RequestError::Banned(remaining)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Ban {
+    until: Instant,
+    rate_limited: bool,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+fn duration_to_secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1_000_000_000.0
+}
+
+fn secs_to_duration(secs: f64) -> Duration {
+    let whole = secs.trunc();
+    Duration::new(whole as u64, ((secs - whole) * 1_000_000_000.0) as u32)
+}
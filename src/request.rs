@@ -1,15 +1,31 @@
+use endpoint::Endpoint;
 use error::RequestError;
-use MAL;
-use reqwest::{RequestBuilder, Response, StatusCode, Url};
-use reqwest::header::{ContentType, Headers};
+use rate_limit::BanTracker;
+use reqwest::{Method, StatusCode, Url};
+use reqwest::header::{Authorization, Basic, Bearer, ContentType, RetryAfter};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use transport::{HttpRequest, HttpResponse};
+use v2;
+use {Api, MAL};
 
 lazy_static! {
-    static ref BASE_URL: Url = Url::parse("https://myanimelist.net").unwrap();
+    pub static ref BASE_URL: Url = Url::parse("https://myanimelist.net").unwrap();
 }
 
+// MAL doesn't usually tell us how long a ban lasts, so these are used as estimates instead
+const BAN_DURATION_SECS: u64 = 2 * 60 * 60;
+const RATE_LIMIT_DURATION_SECS: u64 = 60;
+
+// Phrases found in the page the legacy API responds with when the caller is banned
+const BAN_PAGE_MARKERS: [&str; 3] = ["banned", "too many requests", "access has been restricted"];
+
+/// Represents the type of list a request operates on.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum ListType {
+    /// A user's anime list.
     Anime,
+    /// A user's manga list.
     Manga,
 }
 
@@ -22,6 +38,7 @@ pub type Body<'a> = &'a str;
 pub enum Request<'a> {
     Search(Name<'a>, ListType),
     List(Username<'a>, ListType),
+    Next(&'a Url),
     Add(ID, ListType, Body<'a>),
     Update(ID, ListType, Body<'a>),
     Delete(ID, ListType),
@@ -29,22 +46,29 @@ pub enum Request<'a> {
 }
 
 impl<'a> Request<'a> {
-    fn send_req(self, mal: &MAL) -> Result<Response, RequestError> {
-        let mut url = BASE_URL.clone();
+    pub fn build(&self, mal: &MAL) -> HttpRequest {
+        match mal.api {
+            Api::Legacy => self.build_legacy(mal),
+            Api::V2 { .. } => v2::build_request(self, mal).with_auth(mal),
+        }
+    }
+
+    fn build_legacy(&self, mal: &MAL) -> HttpRequest {
+        let url = |endpoint, id| mal.endpoints.url(&mal.base_url, endpoint, id);
         use self::Request::*;
 
-        match self {
+        match *self {
             Search(name, list_type) => {
-                match list_type {
-                    ListType::Anime => url.set_path("/api/anime/search.xml"),
-                    ListType::Manga => url.set_path("/api/manga/search.xml"),
-                }
+                let mut url = match list_type {
+                    ListType::Anime => url(Endpoint::AnimeSearch, None),
+                    ListType::Manga => url(Endpoint::MangaSearch, None),
+                };
 
                 url.query_pairs_mut().append_pair("q", name);
-                mal.client.get(url).with_auth(mal).send_req()
+                HttpRequest::new(Method::Get, url).with_auth(mal)
             }
             List(uname, list_type) => {
-                url.set_path("/malappinfo.php");
+                let mut url = url(Endpoint::List, None);
 
                 let query = match list_type {
                     ListType::Anime => "anime",
@@ -56,79 +80,174 @@ impl<'a> Request<'a> {
                     .append_pair("status", "all")
                     .append_pair("type", query);
 
-                mal.client.get(url).send_req()
+                HttpRequest::new(Method::Get, url)
             }
+            Next(url) => HttpRequest::new(Method::Get, url.clone()),
             Add(id, list_type, body) => {
-                match list_type {
-                    ListType::Anime => url.set_path(&format!("/api/animelist/add/{}.xml", id)),
-                    ListType::Manga => url.set_path(&format!("/api/mangalist/add/{}.xml", id)),
-                }
+                let url = match list_type {
+                    ListType::Anime => url(Endpoint::AnimeAdd, Some(id)),
+                    ListType::Manga => url(Endpoint::MangaAdd, Some(id)),
+                };
 
-                mal.client
-                    .post(url)
+                HttpRequest::new(Method::Post, url)
                     .with_body(body)
                     .with_auth(mal)
-                    .send_req()
             }
             Update(id, list_type, body) => {
-                match list_type {
-                    ListType::Anime => url.set_path(&format!("/api/animelist/update/{}.xml", id)),
-                    ListType::Manga => url.set_path(&format!("/api/mangalist/update/{}.xml", id)),
-                }
+                let url = match list_type {
+                    ListType::Anime => url(Endpoint::AnimeUpdate, Some(id)),
+                    ListType::Manga => url(Endpoint::MangaUpdate, Some(id)),
+                };
 
-                mal.client
-                    .post(url)
+                HttpRequest::new(Method::Post, url)
                     .with_body(body)
                     .with_auth(mal)
-                    .send_req()
             }
             Delete(id, list_type) => {
-                match list_type {
-                    ListType::Anime => url.set_path(&format!("/api/animelist/delete/{}.xml", id)),
-                    ListType::Manga => url.set_path(&format!("/api/mangalist/delete/{}.xml", id)),
-                }
+                let url = match list_type {
+                    ListType::Anime => url(Endpoint::AnimeDelete, Some(id)),
+                    ListType::Manga => url(Endpoint::MangaDelete, Some(id)),
+                };
 
-                mal.client.delete(url).with_auth(mal).send_req()
+                HttpRequest::new(Method::Delete, url).with_auth(mal)
             }
             VerifyCredentials => {
-                url.set_path("/api/account/verify_credentials.xml");
-                mal.client.get(url).with_auth(mal).send_req()
+                let url = url(Endpoint::VerifyCredentials, None);
+                HttpRequest::new(Method::Get, url).with_auth(mal)
             }
         }
     }
 
+    // Returns true if sending the request multiple times has the same effect as sending it once
+    fn is_idempotent(&self) -> bool {
+        match *self {
+            Request::Add(..) => false,
+            _ => true,
+        }
+    }
+
     pub fn send(self, mal: &MAL) -> Result<String, RequestError> {
-        self.send_req(mal)?
-            .text()
-            .map_err(RequestError::ReadResponse)
+        let policy = &mal.retry_policy;
+        let can_retry = self.is_idempotent() || policy.retry_non_idempotent;
+
+        if let Some(ref oauth) = mal.oauth {
+            oauth
+                .refresh_if_needed(&*mal.transport)
+                .map_err(|err| RequestError::TokenRefresh(Box::new(err)))?;
+        }
+
+        let req = self.build(mal);
+
+        let mut attempt = 1;
+
+        loop {
+            match req.clone().send_req(mal) {
+                Ok(resp) => return Ok(resp.body),
+                Err(ref err)
+                    if can_retry && attempt < policy.max_attempts && policy.is_transient(err) =>
+                {
+                    thread::sleep(policy.delay_for(attempt));
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
     }
 }
 
 trait RequestExt {
-    fn with_auth(&mut self, mal: &MAL) -> &mut RequestBuilder;
-    fn with_body(&mut self, body: &str) -> &mut RequestBuilder;
+    fn with_auth(self, mal: &MAL) -> HttpRequest;
+    fn with_body(self, body: &str) -> HttpRequest;
 
-    fn send_req(&mut self) -> Result<Response, RequestError>;
+    fn send_req(self, mal: &MAL) -> Result<HttpResponse, RequestError>;
 }
 
-impl RequestExt for RequestBuilder {
-    fn with_auth(&mut self, mal: &MAL) -> &mut RequestBuilder {
-        self.basic_auth(mal.username.clone(), Some(mal.password.clone()))
+impl RequestExt for HttpRequest {
+    fn with_auth(mut self, mal: &MAL) -> HttpRequest {
+        match mal.api {
+            Api::Legacy => self.headers.set(Authorization(Basic {
+                username: mal.username.clone(),
+                password: Some(mal.password.clone()),
+            })),
+            Api::V2 {
+                ref client_id,
+                ref access_token,
+            } => {
+                let access_token = match mal.oauth {
+                    Some(ref oauth) => Some(oauth.access_token()),
+                    None => access_token.clone(),
+                };
+
+                match access_token {
+                    Some(token) => self.headers.set(Authorization(Bearer { token })),
+                    None => self.headers.set_raw("X-MAL-CLIENT-ID", client_id.clone()),
+                }
+            }
+        }
+
+        self
     }
 
-    fn with_body(&mut self, body: &str) -> &mut RequestBuilder {
-        let mut headers = Headers::new();
-        headers.set(ContentType::form_url_encoded());
+    fn with_body(mut self, body: &str) -> HttpRequest {
+        self.headers.set(ContentType::form_url_encoded());
+        self.body = Some(format!("data={}", body));
+        self
+    }
 
-        self.body(format!("data={}", body)).headers(headers)
+    fn send_req(self, mal: &MAL) -> Result<HttpResponse, RequestError> {
+        mal.ban_tracker.check()?;
+
+        if let Some(ref limiter) = mal.rate_limiter {
+            limiter.acquire()?;
+        }
+
+        let resp = mal.transport.send(self)?;
+        check_response(resp, &mal.api, &mal.ban_tracker)
+    }
+}
+
+pub fn check_response(
+    resp: HttpResponse,
+    api: &Api,
+    bans: &BanTracker,
+) -> Result<HttpResponse, RequestError> {
+    match resp.status {
+        StatusCode::Ok | StatusCode::Created => Ok(resp),
+        StatusCode::Forbidden if is_ban(&resp, api) => {
+            let retry_after = retry_after(&resp, BAN_DURATION_SECS);
+            bans.ban_for(retry_after);
+            Err(RequestError::Banned(retry_after))
+        }
+        StatusCode::TooManyRequests => {
+            let retry_after = retry_after(&resp, RATE_LIMIT_DURATION_SECS);
+            bans.rate_limit_for(retry_after);
+            Err(RequestError::RateLimited(retry_after))
+        }
+        status => Err(RequestError::BadResponseCode(status)),
     }
+}
 
-    fn send_req(&mut self) -> Result<Response, RequestError> {
-        let resp = self.send().map_err(RequestError::HttpError)?;
+// MAL also responds with a 403 to requests the caller isn't allowed to make, so it's only treated
+// as a ban when the legacy API says when to retry or responds with its ban page.
+// Version 2 of the API only uses it for requests the access token doesn't allow.
+fn is_ban(resp: &HttpResponse, api: &Api) -> bool {
+    match *api {
+        Api::Legacy => {
+            let body = resp.body.to_lowercase();
 
-        match resp.status() {
-            StatusCode::Ok | StatusCode::Created => Ok(resp),
-            status => Err(RequestError::BadResponseCode(status)),
+            resp.headers.has::<RetryAfter>()
+                || BAN_PAGE_MARKERS.iter().any(|marker| body.contains(marker))
         }
+        Api::V2 { .. } => false,
+    }
+}
+
+fn retry_after(resp: &HttpResponse, default_secs: u64) -> Duration {
+    match resp.headers.get::<RetryAfter>() {
+        Some(&RetryAfter::Delay(delay)) => delay,
+        Some(&RetryAfter::DateTime(date)) => SystemTime::from(date)
+            .duration_since(SystemTime::now())
+            .unwrap_or_else(|_| Duration::from_secs(0)),
+        None => Duration::from_secs(default_secs),
     }
 }
@@ -0,0 +1,172 @@
+//! This module provides the [`RetryPolicy`] struct, which determines how requests that fail
+//! because of transient errors are retried.
+//!
+//! Requests that add an entry to a user's list are not retried by default, as MAL may have
+//! processed the original request even if a response was never received.
+//!
+//! [`RetryPolicy`]: ./struct.RetryPolicy.html
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate mal;
+//! extern crate reqwest;
+//!
+//! use mal::MAL;
+//! use mal::error::RequestError;
+//! use mal::retry::RetryPolicy;
+//! use mal::transport::{HttpRequest, HttpResponse, Transport};
+//! use reqwest::StatusCode;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::time::Duration;
+//!
+//! // A transport that fails the first request it receives
+//! #[derive(Debug, Default)]
+//! struct FlakyTransport {
+//!     attempts: AtomicUsize,
+//! }
+//!
+//! impl Transport for FlakyTransport {
+//!     fn send(&self, _: HttpRequest) -> Result<HttpResponse, RequestError> {
+//!         match self.attempts.fetch_add(1, Ordering::SeqCst) {
+//!             0 => Ok(HttpResponse::new(StatusCode::ServiceUnavailable, "")),
+//!             _ => Ok(HttpResponse::new(StatusCode::Ok, "<user><id>1</id></user>")),
+//!         }
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let mut mal = MAL::with_transport("username", "password", FlakyTransport::default());
+//!
+//! mal.retry_policy = RetryPolicy {
+//!     base_delay: Duration::from_millis(10),
+//!     ..RetryPolicy::default()
+//! };
+//!
+//! assert_eq!(mal.verify_credentials().unwrap(), true);
+//! # }
+//! ```
+
+use error::RequestError;
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Determines how failed requests are retried.
+///
+/// Every retried request still goes through the [`RateLimiter`] of the [`MAL`] instance
+/// that sent it.
+///
+/// [`RateLimiter`]: ../rate_limit/struct.RateLimiter.html
+/// [`MAL`]: ../struct.MAL.html
+///
+/// # Examples
+///
+/// ```
+/// use mal::retry::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy {
+///     max_attempts: 5,
+///     base_delay: Duration::from_secs(1),
+///     jitter: 0.0,
+///     ..RetryPolicy::default()
+/// };
+///
+/// assert_eq!(policy.delay_for(1), Duration::from_secs(1));
+/// assert_eq!(policy.delay_for(2), Duration::from_secs(2));
+/// assert_eq!(policy.delay_for(3), Duration::from_secs(4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of times a request will be sent, including the first attempt.
+    pub max_attempts: u32,
+    /// The delay before the first retry. Each subsequent retry doubles the delay.
+    pub base_delay: Duration,
+    /// The maximum delay between retries.
+    pub max_delay: Duration,
+    /// The fraction of each delay that is randomized, from 0.0 to 1.0.
+    /// A value of 0.5 means that each delay will be randomly shortened by up to half.
+    pub jitter: f64,
+    /// The response status codes that will cause a request to be retried.
+    pub transient_statuses: Vec<StatusCode>,
+    /// Whether or not requests that fail to be sent or whose response can't be read
+    /// (such as from a connection reset) should be retried.
+    pub retry_http_errors: bool,
+    /// Whether or not requests that are not safe to send multiple times should be retried.
+    /// Currently, this only applies to adding entries to a user's list.
+    pub retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    /// Returns a `RetryPolicy` that never retries requests.
+    #[inline]
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Returns the amount of time to wait before sending the specified retry,
+    /// where a value of 1 is the first retry.
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let multiplier = 1u32
+            .checked_shl(retry.saturating_sub(1))
+            .unwrap_or(u32::max_value());
+
+        let delay = self.base_delay
+            .checked_mul(multiplier)
+            .map(|delay| delay.min(self.max_delay))
+            .unwrap_or(self.max_delay);
+
+        let jitter = self.jitter.max(0.0).min(1.0);
+
+        if jitter > 0.0 {
+            let scale = 1.0 - jitter * random_fraction();
+            let nanos = (delay.as_secs() as f64 * 1e9 + f64::from(delay.subsec_nanos())) * scale;
+            Duration::new((nanos / 1e9) as u64, (nanos % 1e9) as u32)
+        } else {
+            delay
+        }
+    }
+
+    /// Returns true if the specified error should cause a request to be retried.
+    pub fn is_transient(&self, err: &RequestError) -> bool {
+        match *err {
+            RequestError::HttpError(_) | RequestError::ReadResponse(_) => self.retry_http_errors,
+            RequestError::BadResponseCode(status) => self.transient_statuses.contains(&status),
+            _ => false,
+        }
+    }
+}
+
+// Returns a number from 0.0 up to (but not including) 1.0.
+// Jitter only needs to differ between calls, so the randomly seeded keys of the standard
+// library's hasher are enough here
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+impl Default for RetryPolicy {
+    /// Returns a `RetryPolicy` that sends a request up to 3 times, starting with a 2 second delay.
+    /// Requests are retried on connection errors, as well as 500, 502, 503, and 504 responses.
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(60),
+            jitter: 0.5,
+            transient_statuses: vec![
+                StatusCode::InternalServerError,
+                StatusCode::BadGateway,
+                StatusCode::ServiceUnavailable,
+                StatusCode::GatewayTimeout,
+            ],
+            retry_http_errors: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
@@ -0,0 +1,703 @@
+//! This module provides [`MockServer`], an in-process stand-in for the legacy MyAnimeList API.
+//!
+//! The server listens on a local port and implements every endpoint the [`MAL`] struct uses:
+//! searching, reading a user's list, adding / updating / deleting list entries, and verifying
+//! credentials. Lists are kept in memory, and requests that need authorization are checked
+//! against the username and password the server was created with.
+//!
+//! This makes it possible to test code that uses this library without depending on the live site.
+//!
+//! This module is only available with the `testing` feature.
+//!
+//! [`MockServer`]: ./struct.MockServer.html
+//! [`MAL`]: ../struct.MAL.html
+//!
+//! # Examples
+//!
+//! ```
+//! use mal::list::Status;
+//! use mal::list::anime::{AiringStatus, AnimeInfo, AnimeType, AnimeValues};
+//! use mal::testing::MockServer;
+//!
+//! let server = MockServer::new("username", "password").unwrap();
+//!
+//! server.add_anime(AnimeInfo {
+//!     id: 4224,
+//!     title: "Toradora!".into(),
+//!     english_title: Some("Toradora!".into()),
+//!     synonyms: Vec::new(),
+//!     episodes: 25,
+//!     series_type: AnimeType::TV,
+//!     airing_status: AiringStatus::FinishedAiring,
+//!     start_date: None,
+//!     end_date: None,
+//!     synopsis: None,
+//!     image_url: String::new(),
+//! });
+//!
+//! // Create a MAL instance that sends requests to the server
+//! let mal = server.mal();
+//! assert_eq!(mal.verify_credentials().unwrap(), true);
+//!
+//! let found = mal.anime_list().search_for("toradora").unwrap();
+//! assert_eq!(found.len(), 1);
+//!
+//! let mut values = AnimeValues::new();
+//! values.set_watched_episodes(5).set_status(Status::WatchingOrReading);
+//!
+//! mal.anime_list().add_id(found[0].id, &mut values).unwrap();
+//!
+//! let list = mal.anime_list().read().unwrap();
+//! assert_eq!(list.entries[0].values.watched_episodes(), 5);
+//! assert_eq!(list.user_info.watching, 1);
+//! ```
+
+use base64;
+use chrono::{NaiveDate, Utc};
+use minidom::Element;
+use MAL;
+use reqwest::Url;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+use url::form_urlencoded;
+
+#[cfg(feature = "anime")]
+use list::anime::AnimeInfo;
+#[cfg(feature = "manga")]
+use list::manga::MangaInfo;
+
+// Describes how the entries of one type of list are represented by the legacy API
+#[derive(Debug)]
+struct ListKind {
+    name: &'static str,
+    // The tags of an add / update request, the tags they map to on a list entry,
+    // and the values entries start out with
+    values: &'static [(&'static str, &'static str, &'static str)],
+    // The tags of the user info that hold the number of entries with each status
+    status_counts: &'static [(&'static str, &'static str)],
+}
+
+static ANIME: ListKind = ListKind {
+    name: "anime",
+    values: &[
+        ("episode", "my_watched_episodes", "0"),
+        ("status", "my_status", "1"),
+        ("date_start", "my_start_date", "0000-00-00"),
+        ("date_finish", "my_finish_date", "0000-00-00"),
+        ("score", "my_score", "0"),
+        ("enable_rewatching", "my_rewatching", "0"),
+        ("tags", "my_tags", ""),
+        ("storage_type", "my_storage_type", "0"),
+        ("storage_value", "my_storage_value", "0"),
+        ("times_rewatched", "my_times_rewatched", "0"),
+        ("rewatch_value", "my_rewatch_value", "0"),
+        ("priority", "my_priority", "0"),
+        ("enable_discussion", "my_enable_discussion", "0"),
+        ("comments", "my_comments", ""),
+        ("downloaded_episodes", "my_downloaded_episodes", "0"),
+    ],
+    status_counts: &[
+        ("1", "user_watching"),
+        ("2", "user_completed"),
+        ("3", "user_onhold"),
+        ("4", "user_dropped"),
+        ("6", "user_plantowatch"),
+    ],
+};
+
+static MANGA: ListKind = ListKind {
+    name: "manga",
+    values: &[
+        ("chapter", "my_read_chapters", "0"),
+        ("volume", "my_read_volumes", "0"),
+        ("status", "my_status", "1"),
+        ("score", "my_score", "0"),
+        ("date_start", "my_start_date", "0000-00-00"),
+        ("date_finish", "my_finish_date", "0000-00-00"),
+        ("enable_rereading", "my_rereadingg", "0"),
+        ("tags", "my_tags", ""),
+        ("times_reread", "my_times_reread", "0"),
+        ("reread_value", "my_reread_value", "0"),
+        ("priority", "my_priority", "0"),
+        ("enable_discussion", "my_enable_discussion", "0"),
+        ("comments", "my_comments", ""),
+        ("scan_group", "my_scan_group", ""),
+        ("retail_volumes", "my_retail_volumes", "0"),
+    ],
+    status_counts: &[
+        ("1", "user_reading"),
+        ("2", "user_completed"),
+        ("3", "user_onhold"),
+        ("4", "user_dropped"),
+        ("6", "user_plantoread"),
+    ],
+};
+
+type Fields = Vec<(&'static str, String)>;
+
+#[derive(Debug)]
+struct MockSeries {
+    id: u32,
+    titles: Vec<String>,
+    search_fields: Fields,
+    list_fields: Fields,
+}
+
+#[derive(Debug, Default)]
+struct MockList {
+    series: Vec<MockSeries>,
+    entries: BTreeMap<u32, BTreeMap<&'static str, String>>,
+}
+
+#[derive(Debug)]
+struct State {
+    username: String,
+    password: String,
+    anime: MockList,
+    manga: MockList,
+}
+
+impl State {
+    fn list(&mut self, name: &str) -> Option<(&'static ListKind, &mut MockList)> {
+        match name {
+            "anime" => Some((&ANIME, &mut self.anime)),
+            "manga" => Some((&MANGA, &mut self.manga)),
+            _ => None,
+        }
+    }
+}
+
+/// An in-process HTTP server that behaves like the legacy MyAnimeList API.
+///
+/// The server is shut down when it is dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    requests: Arc<AtomicUsize>,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a new `MockServer` on an unused local port.
+    ///
+    /// Requests that need authorization will only succeed with the specified username and password.
+    pub fn new<S: Into<String>>(username: S, password: S) -> io::Result<MockServer> {
+        let listener = TcpListener::bind(SocketAddr::new(Ipv4Addr::new(127, 0, 0, 1).into(), 0))?;
+        let addr = listener.local_addr()?;
+
+        let state = Arc::new(Mutex::new(State {
+            username: username.into(),
+            password: password.into(),
+            anime: MockList::default(),
+            manga: MockList::default(),
+        }));
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let state = Arc::clone(&state);
+            let requests = Arc::clone(&requests);
+            let shutdown = Arc::clone(&shutdown);
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    if let Ok(stream) = stream {
+                        requests.fetch_add(1, Ordering::SeqCst);
+                        // A client that hangs up early shouldn't take the server down with it
+                        let _ = handle_connection(&stream, &state);
+                    }
+                }
+            })
+        };
+
+        Ok(MockServer {
+            addr,
+            state,
+            requests,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the URL the server can be reached at.
+    #[inline]
+    pub fn base_url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.addr)).unwrap()
+    }
+
+    /// Returns a new [`MAL`] instance that sends requests to the server with the credentials
+    /// the server was created with.
+    ///
+    /// Rate limiting is disabled on the returned instance.
+    ///
+    /// [`MAL`]: ../struct.MAL.html
+    pub fn mal(&self) -> MAL<'static> {
+        let state = self.state.lock().unwrap();
+
+        let mut mal = MAL::new(state.username.clone(), state.password.clone());
+        mal.base_url = self.base_url();
+        mal.rate_limiter = None;
+        mal
+    }
+
+    /// Returns the number of requests the server has received.
+    #[inline]
+    pub fn requests_received(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    /// Makes an anime series available to search for and add to the user's list.
+    #[cfg(feature = "anime")]
+    pub fn add_anime(&self, info: AnimeInfo) {
+        let series = MockSeries {
+            id: info.id,
+            titles: titles(&info.title, &info.english_title, &info.synonyms),
+            search_fields: vec![
+                ("id", info.id.to_string()),
+                ("title", info.title.clone()),
+                ("english", info.english_title.clone().unwrap_or_default()),
+                ("synonyms", info.synonyms.join("; ")),
+                ("episodes", info.episodes.to_string()),
+                ("type", info.series_type.as_str().into()),
+                ("status", info.airing_status.as_str().into()),
+                ("start_date", date_to_str(info.start_date)),
+                ("end_date", date_to_str(info.end_date)),
+                ("synopsis", info.synopsis.clone().unwrap_or_default()),
+                ("image", info.image_url.clone()),
+            ],
+            list_fields: vec![
+                ("series_animedb_id", info.id.to_string()),
+                ("series_title", info.title),
+                ("series_synonyms", info.synonyms.join("; ")),
+                ("series_episodes", info.episodes.to_string()),
+                ("series_type", (info.series_type as i32).to_string()),
+                ("series_status", (info.airing_status as i32).to_string()),
+                ("series_start", date_to_str(info.start_date)),
+                ("series_end", date_to_str(info.end_date)),
+                ("series_image", info.image_url),
+            ],
+        };
+
+        self.state.lock().unwrap().anime.series.push(series);
+    }
+
+    /// Makes a manga series available to search for and add to the user's list.
+    #[cfg(feature = "manga")]
+    pub fn add_manga(&self, info: MangaInfo) {
+        let series = MockSeries {
+            id: info.id,
+            titles: titles(&info.title, &info.english_title, &info.synonyms),
+            search_fields: vec![
+                ("id", info.id.to_string()),
+                ("title", info.title.clone()),
+                ("english", info.english_title.clone().unwrap_or_default()),
+                ("synonyms", info.synonyms.join("; ")),
+                ("chapters", info.chapters.to_string()),
+                ("volumes", info.volumes.to_string()),
+                ("type", info.series_type.as_str().into()),
+                ("status", info.publishing_status.as_str().into()),
+                ("start_date", date_to_str(info.start_date)),
+                ("end_date", date_to_str(info.end_date)),
+                ("synopsis", info.synopsis.clone().unwrap_or_default()),
+                ("image", info.image_url.clone()),
+            ],
+            list_fields: vec![
+                ("series_mangadb_id", info.id.to_string()),
+                ("series_title", info.title),
+                ("series_synonyms", info.synonyms.join("; ")),
+                ("series_chapters", info.chapters.to_string()),
+                ("series_volumes", info.volumes.to_string()),
+                ("series_type", (info.series_type as i32).to_string()),
+                ("series_status", (info.publishing_status as i32).to_string()),
+                ("series_start", date_to_str(info.start_date)),
+                ("series_end", date_to_str(info.end_date)),
+                ("series_image", info.image_url),
+            ],
+        };
+
+        self.state.lock().unwrap().manga.series.push(series);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        // The server thread is blocked waiting for a connection, so give it one to make it
+        // notice the shutdown
+        let _ = TcpStream::connect(self.addr);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MockRequest {
+    method: String,
+    url: Url,
+    authorization: Option<String>,
+    body: String,
+}
+
+fn handle_connection(stream: &TcpStream, state: &Mutex<State>) -> io::Result<()> {
+    let req = read_request(stream)?;
+    let (status, body) = route(&req, &mut state.lock().unwrap());
+
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        _ => "Not Found",
+    };
+
+    let resp = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/xml; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+
+    let mut stream = stream;
+    stream.write_all(resp.as_bytes())?;
+    stream.flush()
+}
+
+fn read_request(stream: &TcpStream) -> io::Result<MockRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+
+    let url = Url::parse("http://localhost")
+        .and_then(|base| base.join(parts.next().unwrap_or("/")))
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid request path"))?;
+
+    let mut authorization = None;
+    let mut content_len = 0;
+
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+
+        let header = header.trim();
+
+        if header.is_empty() {
+            break;
+        }
+
+        let mut split = header.splitn(2, ':');
+        let name = split.next().unwrap_or("").trim().to_ascii_lowercase();
+        let value = split.next().unwrap_or("").trim();
+
+        match name.as_str() {
+            "authorization" => authorization = Some(value.to_string()),
+            "content-length" => content_len = value.parse().unwrap_or(0),
+            _ => (),
+        }
+    }
+
+    let mut body = vec![0; content_len];
+    reader.read_exact(&mut body)?;
+
+    Ok(MockRequest {
+        method,
+        url,
+        authorization,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn route(req: &MockRequest, state: &mut State) -> (u16, String) {
+    let path = req.url.path().trim_matches('/').to_string();
+    let segments = path.split('/').collect::<Vec<_>>();
+
+    match (req.method.as_str(), segments.as_slice()) {
+        ("GET", ["malappinfo.php"]) => read_list(req, state),
+        ("GET", ["api", "account", "verify_credentials.xml"]) => {
+            if !is_authorized(req, state) {
+                return (401, "Invalid credentials".into());
+            }
+
+            let user = element(
+                "user",
+                &[("id", "1".into()), ("username", state.username.clone())],
+            );
+
+            (200, to_xml(&user))
+        }
+        ("GET", ["api", list, "search.xml"]) => {
+            if !is_authorized(req, state) {
+                return (401, "Invalid credentials".into());
+            }
+
+            search(req, list, state)
+        }
+        ("POST", ["api", list, action, file]) | ("DELETE", ["api", list, action, file]) => {
+            if !is_authorized(req, state) {
+                return (401, "Invalid credentials".into());
+            }
+
+            let list = match *list {
+                "animelist" => "anime",
+                "mangalist" => "manga",
+                _ => return (404, "Not Found".into()),
+            };
+
+            match file.split('.').next().unwrap_or("").parse() {
+                Ok(id) => modify_list(req, list, action, id, state),
+                Err(_) => (400, "Invalid ID".into()),
+            }
+        }
+        _ => (404, "Not Found".into()),
+    }
+}
+
+fn is_authorized(req: &MockRequest, state: &State) -> bool {
+    let credentials = req.authorization
+        .as_ref()
+        .and_then(|auth| {
+            let mut split = auth.splitn(2, ' ');
+
+            match (split.next(), split.next()) {
+                (Some("Basic"), Some(encoded)) => base64::decode(encoded).ok(),
+                _ => None,
+            }
+        })
+        .and_then(|decoded| String::from_utf8(decoded).ok());
+
+    credentials == Some(format!("{}:{}", state.username, state.password))
+}
+
+fn search(req: &MockRequest, list: &str, state: &mut State) -> (u16, String) {
+    let query = query_param(&req.url, "q").unwrap_or_default().to_lowercase();
+
+    let (kind, list) = match state.list(list) {
+        Some(list) => list,
+        None => return (404, "Not Found".into()),
+    };
+
+    let mut root = Element::bare(kind.name);
+
+    for series in &list.series {
+        if series.titles.iter().any(|title| title.contains(&query)) {
+            root.append_child(element("entry", &series.search_fields));
+        }
+    }
+
+    // MAL sends back nothing when no series were found
+    if root.children().next().is_none() {
+        return (204, String::new());
+    }
+
+    (200, to_xml(&root))
+}
+
+fn read_list(req: &MockRequest, state: &mut State) -> (u16, String) {
+    let username = query_param(&req.url, "u").unwrap_or_default();
+    let list_type = query_param(&req.url, "type").unwrap_or_default();
+
+    let mut root = Element::bare("myanimelist");
+
+    // MAL sends back an empty list for users that don't exist
+    if !username.eq_ignore_ascii_case(&state.username) {
+        return (200, to_xml(&root));
+    }
+
+    let username = state.username.clone();
+
+    let (kind, list) = match state.list(&list_type) {
+        Some(list) => list,
+        None => return (200, to_xml(&root)),
+    };
+
+    let mut user_info = vec![("user_id", "1".to_string()), ("user_name", username)];
+
+    for &(status, tag) in kind.status_counts {
+        let count = list.entries
+            .values()
+            .filter(|values| values.get("my_status").map(String::as_str) == Some(status))
+            .count();
+
+        user_info.push((tag, count.to_string()));
+    }
+
+    user_info.push(("user_days_spent_watching", "0.00".into()));
+    root.append_child(element("myinfo", &user_info));
+
+    for (id, values) in &list.entries {
+        let series = match list.series.iter().find(|series| series.id == *id) {
+            Some(series) => series,
+            None => continue,
+        };
+
+        let mut fields = series.list_fields.clone();
+        fields.extend(values.iter().map(|(&tag, value)| (tag, value.clone())));
+
+        root.append_child(element(kind.name, &fields));
+    }
+
+    (200, to_xml(&root))
+}
+
+fn modify_list(
+    req: &MockRequest,
+    list: &str,
+    action: &str,
+    id: u32,
+    state: &mut State,
+) -> (u16, String) {
+    let (kind, list) = match state.list(list) {
+        Some(list) => list,
+        None => return (404, "Not Found".into()),
+    };
+
+    if !list.series.iter().any(|series| series.id == id) {
+        return (400, "Invalid ID".into());
+    }
+
+    match action {
+        "add" | "update" => {
+            let values = match parse_body(&req.body) {
+                Some(values) => values,
+                None => return (400, "Invalid XML".into()),
+            };
+
+            let entry = if action == "add" {
+                // Adding an entry that's already on the list doesn't change it
+                if list.entries.contains_key(&id) {
+                    return (201, "Created".into());
+                }
+
+                let defaults = kind.values
+                    .iter()
+                    .map(|&(_, tag, default)| (tag, default.to_string()))
+                    .collect();
+
+                list.entries.entry(id).or_insert(defaults)
+            } else {
+                match list.entries.get_mut(&id) {
+                    Some(entry) => entry,
+                    None => return (200, "Updated".into()),
+                }
+            };
+
+            for elem in values.children() {
+                let tag = match kind.values.iter().find(|value| value.0 == elem.name()) {
+                    Some(&(name, tag, _)) if name.starts_with("date_") => tag,
+                    Some(&(_, tag, _)) => {
+                        entry.insert(tag, normalize_value(tag, &elem.text()));
+                        continue;
+                    }
+                    None => continue,
+                };
+
+                entry.insert(tag, convert_date(&elem.text()));
+            }
+
+            entry.insert("my_last_updated", Utc::now().timestamp().to_string());
+
+            if action == "add" {
+                (201, "Created".into())
+            } else {
+                (200, "Updated".into())
+            }
+        }
+        "delete" => {
+            list.entries.remove(&id);
+            (200, "Deleted".into())
+        }
+        _ => (404, "Not Found".into()),
+    }
+}
+
+// The body of add / update requests is an XML document in the "data" field of a form
+fn parse_body(body: &str) -> Option<Element> {
+    let xml = if body.starts_with("data=<") {
+        body["data=".len()..].to_string()
+    } else {
+        form_urlencoded::parse(body.as_bytes())
+            .find(|&(ref name, _)| name == "data")
+            .map(|(_, value)| value.into_owned())?
+    };
+
+    xml.parse().ok()
+}
+
+fn normalize_value(tag: &str, value: &str) -> String {
+    match tag {
+        // Tags are sent with a trailing delimiter, but aren't read back with one
+        "my_tags" => value
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => value.to_string(),
+    }
+}
+
+// Converts a date from the format used by requests to the one used by lists
+fn convert_date(date: &str) -> String {
+    date_to_str(NaiveDate::parse_from_str(date, "%m%d%Y").ok())
+}
+
+fn date_to_str(date: Option<NaiveDate>) -> String {
+    match date {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => "0000-00-00".into(),
+    }
+}
+
+#[cfg(any(feature = "anime", feature = "manga"))]
+fn titles(title: &str, english_title: &Option<String>, synonyms: &[String]) -> Vec<String> {
+    let mut titles = vec![title.to_lowercase()];
+    titles.extend(english_title.iter().map(|title| title.to_lowercase()));
+    titles.extend(synonyms.iter().map(|title| title.to_lowercase()));
+    titles
+}
+
+fn query_param(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|&(ref key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+fn element(name: &str, fields: &[(&str, String)]) -> Element {
+    let mut elem = Element::bare(name);
+
+    for &(tag, ref value) in fields {
+        let mut child = Element::bare(tag);
+        child.append_text_node(value.as_str());
+        elem.append_child(child);
+    }
+
+    elem
+}
+
+fn to_xml(elem: &Element) -> String {
+    let mut buffer = Vec::new();
+
+    match elem.write_to(&mut buffer) {
+        Ok(()) => String::from_utf8(buffer).unwrap_or_default(),
+        Err(_) => String::new(),
+    }
+}
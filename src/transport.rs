@@ -0,0 +1,224 @@
+//! This module provides the [`Transport`] trait, which is what [`MAL`] uses to actually send
+//! HTTP requests to MyAnimeList.
+//!
+//! By default, requests are sent with a reqwest [`Client`] through the [`ReqwestTransport`] struct.
+//! Implementing [`Transport`] yourself allows you to do things like use an in-memory fake
+//! for testing, or record every request that is made.
+//!
+//! [`Transport`]: ./trait.Transport.html
+//! [`ReqwestTransport`]: ./struct.ReqwestTransport.html
+//! [`MAL`]: ../struct.MAL.html
+//! [`Client`]: ../../reqwest/struct.Client.html
+//!
+//! # Examples
+//!
+//! Using a fake transport that returns the same response for every request:
+//!
+//! ```
+//! extern crate mal;
+//! extern crate reqwest;
+//!
+//! use mal::MAL;
+//! use mal::error::RequestError;
+//! use mal::transport::{HttpRequest, HttpResponse, Transport};
+//! use reqwest::StatusCode;
+//!
+//! #[derive(Debug)]
+//! struct FakeTransport;
+//!
+//! impl Transport for FakeTransport {
+//!     fn send(&self, _: HttpRequest) -> Result<HttpResponse, RequestError> {
+//!         Ok(HttpResponse::new(StatusCode::Ok, "<user><id>1</id></user>"))
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let mal = MAL::with_transport("username", "password", FakeTransport);
+//! assert_eq!(mal.verify_credentials().unwrap(), true);
+//! # }
+//! ```
+
+use error::{ConfigError, RequestError};
+use reqwest::{self, Certificate, Method, Proxy, StatusCode, Url};
+use reqwest::header::{Headers, UserAgent};
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// Represents an HTTP request that is about to be sent to MyAnimeList.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// The HTTP method of the request.
+    pub method: Method,
+    /// The full URL the request is being sent to.
+    pub url: Url,
+    /// The headers of the request. This includes authorization.
+    pub headers: Headers,
+    /// The body of the request, if there is one.
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    /// Creates a new `HttpRequest` with no headers or body.
+    #[inline]
+    pub fn new(method: Method, url: Url) -> HttpRequest {
+        HttpRequest {
+            method,
+            url,
+            headers: Headers::new(),
+            body: None,
+        }
+    }
+}
+
+/// Represents the response MyAnimeList sent back for an [`HttpRequest`].
+///
+/// [`HttpRequest`]: ./struct.HttpRequest.html
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// The status code of the response.
+    pub status: StatusCode,
+    /// The headers of the response.
+    pub headers: Headers,
+    /// The body of the response.
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// Creates a new `HttpResponse` with the specified status code and body, and no headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate mal;
+    /// extern crate reqwest;
+    ///
+    /// use mal::transport::HttpResponse;
+    /// use reqwest::StatusCode;
+    ///
+    /// # fn main() {
+    /// let resp = HttpResponse::new(StatusCode::Created, "Created");
+    ///
+    /// assert_eq!(resp.status, StatusCode::Created);
+    /// assert_eq!(resp.body, "Created");
+    /// # }
+    /// ```
+    #[inline]
+    pub fn new<S: Into<String>>(status: StatusCode, body: S) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: Headers::new(),
+            body: body.into(),
+        }
+    }
+}
+
+/// Used to send HTTP requests to MyAnimeList.
+///
+/// Implementors only need to send the request as-is and report the response back;
+/// checking the status code of the response is handled by [`MAL`].
+///
+/// [`MAL`]: ../struct.MAL.html
+pub trait Transport: Debug + Send + Sync {
+    /// Sends the `request` and returns the response that was received.
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, RequestError>;
+
+    #[doc(hidden)]
+    fn reqwest_client(&self) -> Option<&reqwest::Client> {
+        None
+    }
+}
+
+/// The default [`Transport`], which sends requests with a reqwest [`Client`].
+///
+/// [`Transport`]: ./trait.Transport.html
+/// [`Client`]: ../../reqwest/struct.Client.html
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport<'a> {
+    /// The client used to send requests to the API.
+    pub client: Cow<'a, reqwest::Client>,
+}
+
+impl<'a> ReqwestTransport<'a> {
+    /// Creates a new `ReqwestTransport` that will send requests with the specified `client`.
+    #[inline]
+    pub fn new(client: Cow<'a, reqwest::Client>) -> ReqwestTransport<'a> {
+        ReqwestTransport { client }
+    }
+}
+
+impl<'a> Default for ReqwestTransport<'a> {
+    #[inline]
+    fn default() -> Self {
+        ReqwestTransport::new(Cow::Owned(reqwest::Client::new()))
+    }
+}
+
+impl<'a> Transport for ReqwestTransport<'a> {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, RequestError> {
+        let mut builder = self.client.request(request.method, request.url);
+        builder.headers(request.headers);
+
+        if let Some(body) = request.body {
+            builder.body(body);
+        }
+
+        let mut resp = builder.send().map_err(RequestError::HttpError)?;
+
+        Ok(HttpResponse {
+            status: resp.status(),
+            headers: resp.headers().clone(),
+            body: resp.text().map_err(RequestError::ReadResponse)?,
+        })
+    }
+
+    fn reqwest_client(&self) -> Option<&reqwest::Client> {
+        Some(&self.client)
+    }
+}
+
+// The options used to create the reqwest client of a ReqwestTransport
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ClientOptions {
+    pub timeout: Option<Duration>,
+    pub proxy: Option<String>,
+    pub user_agent: Option<String>,
+    pub root_certificates: Vec<Vec<u8>>,
+}
+
+impl ClientOptions {
+    pub fn is_default(&self) -> bool {
+        self.timeout.is_none() && self.proxy.is_none() && self.user_agent.is_none()
+            && self.root_certificates.is_empty()
+    }
+
+    pub fn build(self) -> Result<reqwest::Client, ConfigError> {
+        let mut builder = reqwest::Client::builder();
+
+        if let Some(timeout) = self.timeout {
+            builder.timeout(timeout);
+        }
+
+        if let Some(proxy) = self.proxy {
+            let proxy = Url::parse(&proxy)
+                .ok()
+                .and_then(|url| Proxy::all(url).ok())
+                .ok_or_else(|| ConfigError::InvalidProxy(proxy))?;
+
+            builder.proxy(proxy);
+        }
+
+        if let Some(user_agent) = self.user_agent {
+            let mut headers = Headers::new();
+            headers.set(UserAgent::new(user_agent));
+            builder.default_headers(headers);
+        }
+
+        for cert in self.root_certificates {
+            let cert = Certificate::from_der(&cert).map_err(ConfigError::InvalidCertificate)?;
+            builder.add_root_certificate(cert);
+        }
+
+        builder.build().map_err(ConfigError::BuildClient)
+    }
+}
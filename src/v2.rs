@@ -0,0 +1,308 @@
+// Contains the functionality required to talk to version 2 of the MyAnimeList API,
+// which uses JSON instead of XML and authenticates with OAuth2.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use endpoint;
+use error::{ListError, MALError, RequestError};
+use list::{ListEntries, ListEntry, SeriesInfo, Status};
+use MAL;
+use request::{ListType, Request};
+use reqwest::{Method, Url};
+use reqwest::header::ContentType;
+use serde_json::{self, Value};
+use transport::HttpRequest;
+use url::form_urlencoded;
+
+lazy_static! {
+    pub static ref BASE_URL: Url = Url::parse("https://api.myanimelist.net").unwrap();
+}
+
+const SEARCH_LIMIT: &str = "100";
+const LIST_PAGE_LIMIT: &str = "1000";
+
+const ANIME_FIELDS: &str = "id,title,alternative_titles,num_episodes,media_type,status,\
+                            start_date,end_date,main_picture";
+
+const ANIME_STATUS_FIELDS: &str = "status,score,num_episodes_watched,is_rewatching,start_date,\
+                                   finish_date,priority,num_times_rewatched,rewatch_value,tags,\
+                                   comments,updated_at";
+
+const MANGA_STATUS_FIELDS: &str = "status,score,num_volumes_read,num_chapters_read,is_rereading,\
+                                   start_date,finish_date,priority,num_times_reread,\
+                                   reread_value,tags,comments,updated_at";
+
+const MANGA_FIELDS: &str = "id,title,alternative_titles,num_chapters,num_volumes,media_type,\
+                            status,start_date,end_date,main_picture";
+
+fn list_name(list_type: ListType) -> &'static str {
+    match list_type {
+        ListType::Anime => "anime",
+        ListType::Manga => "manga",
+    }
+}
+
+fn series_fields(list_type: ListType) -> &'static str {
+    match list_type {
+        ListType::Anime => ANIME_FIELDS,
+        ListType::Manga => MANGA_FIELDS,
+    }
+}
+
+fn status_fields(list_type: ListType) -> &'static str {
+    match list_type {
+        ListType::Anime => ANIME_STATUS_FIELDS,
+        ListType::Manga => MANGA_STATUS_FIELDS,
+    }
+}
+
+pub fn build_request(req: &Request, mal: &MAL) -> HttpRequest {
+    let url_for = |path: &str| endpoint::join_path(&mal.v2_base_url, path);
+    use request::Request::*;
+
+    match *req {
+        Search(name, list_type) => {
+            let mut url = url_for(&format!("/v2/{}", list_name(list_type)));
+
+            url.query_pairs_mut()
+                .append_pair("q", name)
+                .append_pair("limit", SEARCH_LIMIT)
+                .append_pair("fields", &format!("{},synopsis", series_fields(list_type)));
+
+            HttpRequest::new(Method::Get, url)
+        }
+        List(uname, list_type) => {
+            let mut url = url_for(&format!("/v2/users/{}/{}list", uname, list_name(list_type)));
+
+            url.query_pairs_mut()
+                .append_pair(
+                    "fields",
+                    &format!(
+                        "list_status{{{}}},{}",
+                        status_fields(list_type),
+                        series_fields(list_type)
+                    ),
+                )
+                .append_pair("limit", LIST_PAGE_LIMIT)
+                .append_pair("nsfw", "true");
+
+            HttpRequest::new(Method::Get, url)
+        }
+        Next(next_url) => {
+            // The URL of the next page always points to MAL, so only its path and query are used
+            let mut url = url_for(next_url.path());
+            url.set_query(next_url.query());
+
+            HttpRequest::new(Method::Get, url)
+        }
+        Add(id, list_type, body) | Update(id, list_type, body) => {
+            let url = url_for(&format!("/v2/{}/{}/my_list_status", list_name(list_type), id));
+
+            let mut req = HttpRequest::new(Method::Patch, url);
+            req.headers.set(ContentType::form_url_encoded());
+            req.body = Some(body.into());
+            req
+        }
+        Delete(id, list_type) => {
+            let url = url_for(&format!("/v2/{}/{}/my_list_status", list_name(list_type), id));
+            HttpRequest::new(Method::Delete, url)
+        }
+        VerifyCredentials => {
+            HttpRequest::new(Method::Get, url_for("/v2/users/@me"))
+        }
+    }
+}
+
+pub fn parse_search_results<E: ListEntry>(
+    resp: Result<String, RequestError>,
+) -> Result<Vec<E::Info>, MALError> {
+    let resp = resp.map_err(MALError::Request)?;
+    let root: Value = serde_json::from_str(&resp).map_err(MALError::Json)?;
+
+    let mut entries = Vec::new();
+
+    for item in parse_array(&root, "data").map_err(MALError::List)? {
+        let node = field(item, "node").map_err(MALError::List)?;
+        let entry = E::Info::from_json(node).map_err(MALError::List)?;
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+// Parses a single page of a user's list, and returns the URL of the next page if there is one
+pub fn parse_list_page<E: ListEntry>(resp: &str) -> Result<(Vec<E>, Option<Url>), MALError> {
+    let root: Value = serde_json::from_str(resp).map_err(MALError::Json)?;
+
+    let mut entries = Vec::new();
+
+    for item in parse_array(&root, "data").map_err(MALError::List)? {
+        let entry = E::from_json(item).map_err(MALError::List)?;
+        entries.push(entry);
+    }
+
+    let next = match root.get("paging").and_then(|paging| paging.get("next")) {
+        Some(&Value::String(ref next)) => Some(
+            Url::parse(next)
+                .map_err(|_| MALError::List(ListError::JSONConversionFailed("next".into())))?,
+        ),
+        _ => None,
+    };
+
+    Ok((entries, next))
+}
+
+pub fn read_list<E: ListEntry>(mal: &MAL, username: &str) -> Result<ListEntries<E>, MALError> {
+    let mut resp = Request::List(username, E::list_type())
+        .send(mal)
+        .map_err(MALError::Request)?;
+
+    let mut entries = Vec::new();
+
+    loop {
+        let (page, next) = parse_list_page::<E>(&resp)?;
+        entries.extend(page);
+
+        match next {
+            Some(url) => {
+                resp = Request::Next(&url).send(mal).map_err(MALError::Request)?;
+            }
+            None => break,
+        }
+    }
+
+    Ok(ListEntries {
+        user_info: E::user_info_from_entries(&entries),
+        entries,
+    })
+}
+
+pub fn encode_form(fields: &[(&str, String)]) -> String {
+    form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(fields)
+        .finish()
+}
+
+pub fn field<'a>(json: &'a Value, name: &str) -> Result<&'a Value, ListError> {
+    match json.get(name) {
+        Some(&Value::Null) | None => Err(ListError::MissingJSONField(name.into())),
+        Some(value) => Ok(value),
+    }
+}
+
+pub fn parse_u32(json: &Value, name: &str) -> Result<u32, ListError> {
+    field(json, name)?
+        .as_u64()
+        .map(|value| value as u32)
+        .ok_or_else(|| ListError::JSONConversionFailed(name.into()))
+}
+
+pub fn parse_bool(json: &Value, name: &str) -> Result<bool, ListError> {
+    field(json, name)?
+        .as_bool()
+        .ok_or_else(|| ListError::JSONConversionFailed(name.into()))
+}
+
+pub fn parse_string(json: &Value, name: &str) -> Result<String, ListError> {
+    field(json, name)?
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| ListError::JSONConversionFailed(name.into()))
+}
+
+pub fn parse_array<'a>(json: &'a Value, name: &str) -> Result<&'a Vec<Value>, ListError> {
+    field(json, name)?
+        .as_array()
+        .ok_or_else(|| ListError::JSONConversionFailed(name.into()))
+}
+
+pub fn parse_strings(json: &Value, name: &str) -> Vec<String> {
+    match json.get(name).and_then(|value| value.as_array()) {
+        Some(values) => values
+            .iter()
+            .filter_map(|value| value.as_str())
+            .map(String::from)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+// Dates can be partial (such as "2017" or "2017-10"), so the missing parts
+// are filled in with the earliest possible value
+pub fn parse_date(json: &Value, name: &str) -> Option<NaiveDate> {
+    let date = json.get(name)?.as_str()?;
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<u32>().ok());
+
+    let year = parts.next()??;
+    let month = parts.next().unwrap_or(Some(1))?;
+    let day = parts.next().unwrap_or(Some(1))?;
+
+    NaiveDate::from_ymd_opt(year as i32, month, day)
+}
+
+pub fn parse_time(json: &Value, name: &str) -> Result<DateTime<Utc>, ListError> {
+    let time = parse_string(json, name)?;
+
+    DateTime::parse_from_rfc3339(&time)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| ListError::JSONConversionFailed(name.into()))
+}
+
+pub fn parse_status(json: &Value, name: &str) -> Result<Status, ListError> {
+    let status = parse_string(json, name)?;
+    status_from_str(&status).ok_or_else(|| ListError::UnknownStatus(status))
+}
+
+pub fn status_from_str(status: &str) -> Option<Status> {
+    match status {
+        "watching" | "reading" => Some(Status::WatchingOrReading),
+        "completed" => Some(Status::Completed),
+        "on_hold" => Some(Status::OnHold),
+        "dropped" => Some(Status::Dropped),
+        "plan_to_watch" | "plan_to_read" => Some(Status::PlanToWatchOrRead),
+        _ => None,
+    }
+}
+
+pub fn status_to_str(status: Status, list_type: ListType) -> String {
+    let status = match (status, list_type) {
+        (Status::WatchingOrReading, ListType::Anime) => "watching",
+        (Status::WatchingOrReading, ListType::Manga) => "reading",
+        (Status::Completed, _) => "completed",
+        (Status::OnHold, _) => "on_hold",
+        (Status::Dropped, _) => "dropped",
+        (Status::PlanToWatchOrRead, ListType::Anime) => "plan_to_watch",
+        (Status::PlanToWatchOrRead, ListType::Manga) => "plan_to_read",
+    };
+
+    status.into()
+}
+
+pub fn date_to_str(date: Option<NaiveDate>) -> String {
+    match date {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => String::new(),
+    }
+}
+
+pub fn image_url(json: &Value) -> String {
+    json.get("main_picture")
+        .and_then(|pic| pic.get("large").or_else(|| pic.get("medium")))
+        .and_then(|url| url.as_str())
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+pub fn english_title(json: &Value) -> Option<String> {
+    json.get("alternative_titles")
+        .and_then(|titles| titles.get("en"))
+        .and_then(|title| title.as_str())
+        .filter(|title| !title.is_empty())
+        .map(String::from)
+}
+
+pub fn synonyms(json: &Value) -> Vec<String> {
+    match json.get("alternative_titles") {
+        Some(titles) => parse_strings(titles, "synonyms"),
+        None => Vec::new(),
+    }
+}